@@ -89,6 +89,14 @@ impl Complex {
         Complex {   re: 0.0,
                     im: 0.0     }
     }
+    /// Returns the modulus |z| of the complex
+    pub fn modulus(&self) -> f64 {
+        self.re.hypot(self.im)
+    }
+    /// Returns the argument of the complex, in ]-pi, pi]
+    pub fn arg(&self) -> f64 {
+        self.im.atan2(self.re)
+    }
     /// Multiplies the complex by e^{j * theta}
     pub fn expj(theta: f64) -> Self {
         Complex {   re: theta.cos(),
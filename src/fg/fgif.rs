@@ -1,40 +1,589 @@
 extern crate gif;
 
-use gif::{Frame, Encoder, Repeat, SetParameter};
+use gif::{Frame, Encoder, Repeat, SetParameter, DisposalMethod};
 use std::fs::File;
-use std::io::Error;
+use std::io::{self, Error};
 use std::borrow::Cow;
+use std::cell::Cell;
+use std::rc::Rc;
 use std::f64::consts::PI;
 
 use super::fourier::CoeffsSet;
+use super::complex::Complex;
 use super::spline::*;
+use super::color::{self, Rgb};
+use super::sink::{FrameSink, Counter, changed_rect, delay_for};
 
+/// Palette index of the background.
+pub const BACKGROUND: u8 = 0;
+/// Palette index of the arms.
+pub const ARMS: u8 = 1;
+/// Palette index of the epicycles' circles.
+pub const CIRCLES: u8 = 2;
+/// Palette index of the traced drawing.
+pub const TRACE: u8 = 3;
+/// Palette index of the marker at the tip of the last arm.
+pub const TIP: u8 = 4;
+/// Palette index of the original control points.
+pub const POINTS: u8 = 5;
+
+/// Options shared by the drawing functions.
+/// Members:
+///
+/// - `width`, `height` : size of the output, in pixels
+/// - `n_steps`         : number of frames (or of sampled points for a spline)
+/// - `circles`         : wether the circle of each epicycle is drawn
+/// - `order`           : order in which the epicycles are chained
+/// - `anchor`          : where the chain of epicycles starts
+/// - `margin`          : minimal space left around the drawing, in pixels
+/// - `scale`           : pixels per unit, fitted to the output if `None`
+/// - `offset`          : translation applied to the drawing before scaling,
+///                       in units. Centers the drawing if `None`
+/// - `antialias`       : wether lines are anti-aliased, which requires a
+///                       palette built with several levels
+/// - `arm_width`       : stroke width of the arms, in pixels
+/// - `trace_width`     : stroke width of the traced drawing, in pixels
+/// - `trail`           : number of frames after which the traced drawing has
+///                       faded out, kept forever if `None`
+/// - `scheme`          : how the epicycles are coloured
+/// - `show_points`     : wether the original control points are marked
+/// - `fps`             : frames per second of the animation
+/// - `loops`           : number of times the animation is played, 0 meaning
+///                       forever
+/// - `pause`           : number of frames the last frame of the animation
+///                       stays, in addition to its own
+/// - `delta`           : wether only the part of each frame that changed is
+///                       written, instead of the whole frame
+/// - `compare`         : wether the size the output would have had without
+///                       delta frames is computed too, encoding each frame
+///                       twice
+#[allow(clippy::doc_overindented_list_items)]
+pub struct DrawOptions {
+    pub width: usize,
+    pub height: usize,
+    pub n_steps: usize,
+    pub circles: bool,
+    pub order: ArmOrder,
+    pub anchor: Anchor,
+    pub margin: usize,
+    pub scale: Option<f64>,
+    pub offset: Option<(f64, f64)>,
+    pub antialias: bool,
+    pub arm_width: f64,
+    pub trace_width: f64,
+    pub trail: Option<usize>,
+    pub scheme: ColorScheme,
+    pub show_points: bool,
+    pub fps: f64,
+    pub loops: usize,
+    pub pause: usize,
+    pub delta: bool,
+    pub compare: bool,
+}
+
+/// What a drawing function did, for the user to know.
+/// Members:
+///
+/// - `offset`      : offset applied to the drawing, cf `draw_fourier_coeff`
+/// - `bytes`       : size of the output
+/// - `full_bytes`  : size the output would have had without delta frames, if
+///                   they were used and compared
+#[allow(clippy::doc_overindented_list_items)]
+pub struct DrawReport {
+    pub offset: Complex,
+    pub bytes: usize,
+    pub full_bytes: Option<usize>,
+}
+
+/// How the arms and circles of the epicycles are coloured.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ColorScheme {
+    /// All with the `ARMS` and `CIRCLES` inks
+    Mono,
+    /// Each with a hue given by its frequency |k|, from red to purple
+    Frequency,
+}
+
+/// Maximal number of intermediate colours of a fading trail.
+const TRAIL_SHADES: usize = 16;
+
+/// Number of indices of a GIF palette, the last one being kept for the
+/// transparent pixels of delta frames when they are written.
+const MAX_INDICES: usize = 256;
+
+/// Colours of a drawing, laid out in the GIF palette as: the background, then
+/// each ink at full intensity (so that `ARMS`, `CIRCLES`, `TRACE`, `TIP` and
+/// `POINTS` are their indices), then `levels - 1` shades per ink, going from the background to
+/// the ink, used for the partially covered pixels of anti-aliased lines.
+#[derive(Clone)]
+pub struct Palette {
+    background: Rgb,
+    inks: Vec<Rgb>,
+    levels: usize,
+    /// Wether an index is kept for the transparent pixels of delta frames
+    transparent: bool,
+}
+
+impl Palette {
+    /// Builds the palette, `levels` being the number of intensities of each
+    /// ink (1 when there is no anti-aliasing), keeping an index for the
+    /// transparent pixels of GIF delta frames if transparent is set.
+    pub fn new(background: Rgb, inks: Vec<Rgb>, levels: usize, transparent: bool) -> Palette {
+        assert!(levels >= 1);
+        let palette = Palette { background, inks, levels, transparent };
+        assert!(palette.inks.len() * levels < palette.max_indices(),
+                "Too many colors for a GIF palette");
+        palette
+    }
+
+    /* Number of indices the drawing can use */
+    fn max_indices(&self) -> usize {
+        if self.transparent { MAX_INDICES - 1 } else { MAX_INDICES }
+    }
+
+    /// Returns the palette as expected by the GIF encoder, i.e. r, g, b
+    /// components of each colour one after the other.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let bg = self.background;
+        let mut bytes = vec![bg.0, bg.1, bg.2];
+        for ink in self.inks.iter() {
+            bytes.extend_from_slice(&[ink.0, ink.1, ink.2]);
+        }
+        for ink in self.inks.iter() {
+            for l in 1..self.levels {
+                let c = color::mix(bg, *ink, l as f64 / self.levels as f64);
+                bytes.extend_from_slice(&[c.0, c.1, c.2]);
+            }
+        }
+        bytes
+    }
+
+    /// Returns the colour of the full intensity index idx.
+    pub fn color(&self, idx: u8) -> Rgb {
+        if idx == BACKGROUND {  self.background                }
+        else                 {  self.inks[idx as usize - 1]    }
+    }
+
+    /// Appends at most n inks fading from color to the background, as many as
+    /// the palette can hold. Returns the index of the first one, and their
+    /// number.
+    fn add_fade(&mut self, color: u8, n: usize) -> (u8, usize) {
+        let first = self.inks.len() + 1;
+        let n = n.min(self.room());
+        let (bg, ink) = (self.background, self.inks[color as usize - 1]);
+        for f in 1..=n {
+            self.inks.push(color::mix(ink, bg, f as f64 / (n + 1) as f64));
+        }
+        (first as u8, n)
+    }
+
+    /// Appends the ink c, and returns its index.
+    fn add_ink(&mut self, c: Rgb) -> u8 {
+        assert!(self.room() > 0, "Too many colors for a GIF palette");
+        self.inks.push(c);
+        self.inks.len() as u8
+    }
+
+    /// Returns the number of inks that can still be added.
+    fn room(&self) -> usize {
+        (self.max_indices() - 1) / self.levels - self.inks.len()
+    }
+
+    /// Returns the index of the ink whose full intensity index is color, at
+    /// the given level (`levels` being full intensity).
+    fn shade(&self, color: u8, level: usize) -> u8 {
+        if level >= self.levels { return color }
+        let ink = color as usize - 1;
+        (1 + self.inks.len() + ink * (self.levels - 1) + level - 1) as u8
+    }
+
+    /// Returns the full intensity index and the level of the index idx.
+    fn level_of(&self, idx: u8) -> (u8, usize) {
+        let idx = idx as usize;
+        let n = self.inks.len();
+        if idx == BACKGROUND as usize {  (BACKGROUND, 0)          }
+        else if idx <= n             {  (idx as u8, self.levels) }
+        else {
+            let i = idx - n - 1;
+            ((1 + i / (self.levels - 1)) as u8, 1 + i % (self.levels - 1))
+        }
+    }
+}
+
+/// Frame being drawn, whose pixels are numbered row after row, and that is
+/// sent to the output once drawn.
+pub trait Canvas: Clone {
+    /// Returns the frame of the given size, filled with the background.
+    fn blank(width: usize, height: usize) -> Self;
+    /// Paints the whole pixel i with the full intensity index color.
+    fn set(&mut self, i: usize, color: u8);
+    /// Paints the pixel i with color, which covers the share coverage of it,
+    /// in ]0, 1].
+    fn paint(&mut self, i: usize, color: u8, coverage: f64, palette: &Palette);
+    /// Writes the frame in output, for n frame durations.
+    fn write(&self, output: &mut dyn FrameSink, n: usize, palette: &Palette)
+        -> Result<(), Error>;
+}
+
+/// Frame of palette indices, whose partially covered pixels take one of the
+/// `levels` shades of their ink, for the outputs limited to a palette.
+#[derive(Clone)]
+pub struct IndexFrame(Vec<u8>);
+
+impl Canvas for IndexFrame {
+    fn blank(width: usize, height: usize) -> IndexFrame {
+        IndexFrame(vec![BACKGROUND; width * height])
+    }
+
+    fn set(&mut self, i: usize, color: u8) {
+        self.0[i] = color;
+    }
+
+    /// The most covering ink wins, the pixels going to the background.
+    fn paint(&mut self, i: usize, color: u8, coverage: f64, palette: &Palette) {
+        let level = (coverage * palette.levels as f64).round() as usize;
+        if level == 0 { return }
+        let (old, old_level) = palette.level_of(self.0[i]);
+        if (old == color && level > old_level) || (old != color && level >= old_level) {
+            self.0[i] = palette.shade(color, level);
+        }
+    }
+
+    fn write(&self, output: &mut dyn FrameSink, n: usize, _palette: &Palette)
+        -> Result<(), Error> {
+        output.write_frame(&self.0, n)
+    }
+}
+
+/// Frame of colours, for the true colour outputs: an ink is blended with
+/// the colour it is painted over by its exact coverage, without shades nor
+/// a limited number of colours.
+#[derive(Clone)]
+pub struct RgbFrame(Vec<Layer>);
+
+/// Pixel of a `RgbFrame`: the ink last painted on it, covering the share
+/// coverage of the colour under it. An ink painted again, by the next
+/// segment of the same line, keeps its biggest coverage instead of being
+/// blended with itself.
+#[derive(Clone, Copy)]
+struct Layer {
+    under: Rgb,
+    ink: u8,
+    coverage: f64,
+}
+
+impl Layer {
+    fn color(&self, palette: &Palette) -> Rgb {
+        color::mix(self.under, palette.color(self.ink), self.coverage)
+    }
+}
+
+impl Canvas for RgbFrame {
+    /// The background being an ink that covers nothing, the colour under it
+    /// is never seen.
+    fn blank(width: usize, height: usize) -> RgbFrame {
+        RgbFrame(vec![Layer { under: (0, 0, 0), ink: BACKGROUND, coverage: 1.0 };
+                      width * height])
+    }
+
+    fn set(&mut self, i: usize, color: u8) {
+        self.0[i] = Layer { under: self.0[i].under, ink: color, coverage: 1.0 };
+    }
+
+    fn paint(&mut self, i: usize, color: u8, coverage: f64, palette: &Palette) {
+        let layer = &mut self.0[i];
+        if layer.ink == color {
+            layer.coverage = layer.coverage.max(coverage);
+        } else {
+            *layer = Layer { under: layer.color(palette), ink: color, coverage };
+        }
+    }
+
+    fn write(&self, output: &mut dyn FrameSink, n: usize, palette: &Palette)
+        -> Result<(), Error> {
+        let colors: Vec<Rgb> = self.0.iter().map(|l| l.color(palette)).collect();
+        output.write_rgb_frame(&colors, n)
+    }
+}
+
+/// Where the first arm of the chain is attached.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Anchor {
+    /// At c_0, so that the drawing keeps its position relative to the origin
+    Dc,
+    /// At the origin: c_0 is ignored and the drawing is recentered
+    Center,
+}
+
+/// Order in which the epicycles are chained when drawn. It only changes the
+/// way the arms are stacked: the tip of the last one is always the same.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ArmOrder {
+    /// +1, -1, +2, -2, ... as stored in the `CoeffsSet`
+    Index,
+    /// Biggest |c_k| first
+    Magnitude,
+    /// Ascending frequency, from -n to +n
+    Frequency,
+}
+
+/// Returns the terms (k, c_k) of coeffs, sorted as asked by order.
+pub fn ordered_terms(coeffs: &CoeffsSet, order: ArmOrder) -> Vec<(i32, Complex)> {
+    let mut terms = coeffs.terms();
+    match order {
+        ArmOrder::Index => {}
+        ArmOrder::Magnitude => terms.sort_by(|a, b|
+            b.1.modulus().partial_cmp(&a.1.modulus())
+                         .unwrap_or(std::cmp::Ordering::Equal)),
+        ArmOrder::Frequency => terms.sort_by_key(|&(k, _)| k),
+    }
+    terms
+}
+
+/// Bounding box of a set of points, in the drawing's units.
+pub struct BBox {
+    pub min: Complex,
+    pub max: Complex,
+}
+
+impl BBox {
+    /// Returns an empty bounding box.
+    pub fn new() -> BBox {
+        BBox {  min: Complex { re:  f64::INFINITY, im:  f64::INFINITY },
+                max: Complex { re: -f64::INFINITY, im: -f64::INFINITY }  }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.min.re > self.max.re || self.min.im > self.max.im
+    }
+
+    /// Grows the box so that it contains p.
+    pub fn extend(&mut self, p: Complex) {
+        self.min.re = self.min.re.min(p.re);    self.max.re = self.max.re.max(p.re);
+        self.min.im = self.min.im.min(p.im);    self.max.im = self.max.im.max(p.im);
+    }
+
+    /// Grows the box so that it contains the circle of center c and radius r.
+    pub fn extend_circle(&mut self, c: Complex, r: f64) {
+        self.extend(Complex { re: c.re - r, im: c.im - r });
+        self.extend(Complex { re: c.re + r, im: c.im + r });
+    }
+}
+
+/// Transform from the drawing's units to pixels: the point `p` of the drawing
+/// is shown at `center + scale * (p + offset)`, with the y axis flipped.
+pub struct Viewport {
+    pub scale: f64,
+    pub offset: Complex,
+    pub center: (f64, f64),
+}
+
+impl Viewport {
+    /// Builds the viewport that fits bbox in the output described by opts,
+    /// unless `opts.scale` or `opts.offset` force it.
+    pub fn new(bbox: &BBox, opts: &DrawOptions) -> Viewport {
+        let center = (opts.width as f64 / 2.0, opts.height as f64 / 2.0);
+        let (bw, bh) = if bbox.is_empty() { (0.0, 0.0) }
+                       else { (bbox.max.re - bbox.min.re, bbox.max.im - bbox.min.im) };
+
+        let avail_w = opts.width.saturating_sub(2 * opts.margin).max(1) as f64;
+        let avail_h = opts.height.saturating_sub(2 * opts.margin).max(1) as f64;
+        let fitted = (avail_w / bw).min(avail_h / bh);
+        let scale = match opts.scale {
+            Some(s) => s,
+            None if fitted.is_finite() => fitted,
+            None => 1.0, // a single point: nothing to fit
+        };
+
+        let offset = match opts.offset {
+            Some((x, y)) => Complex { re: x, im: y },
+            None if bbox.is_empty() => Complex::zero(),
+            None => Complex::zero() - (bbox.min + bbox.max) / 2.0,
+        };
+
+        Viewport { scale, offset, center }
+    }
+
+    /// Returns the position, in pixels, of the point p of the drawing.
+    pub fn to_pixel(&self, p: Complex) -> (f64, f64) {
+        let q = (p + self.offset) * self.scale;
+        (self.center.0 + q.re, self.center.1 - q.im)
+    }
+}
+
+/// Encoder of full frames, only used to know how big the output would have
+/// been without delta frames.
+struct Shadow {
+    encoder: Encoder<Counter<io::Sink>>,
+    count: Rc<Cell<usize>>,
+}
+
+/// GIF output of the drawing functions.
 #[allow(dead_code)]
-struct MyGif<'a> {
-    encoder: Encoder<&'a mut File>,
+pub struct MyGif {
+    /// The file, until the encoder is created by `start`
+    file: Option<File>,
+    encoder: Option<Encoder<Counter<File>>>,
     width: u16,
     height: u16,
+    /// Frames per second
+    fps: f64,
+    /// Number of times the animation is played, 0 meaning forever
+    loops: usize,
+    /// Wether frames are written as the rectangle that changed
+    delta: bool,
+    /// Wether the full frames are encoded too, to compare the sizes
+    compare: bool,
+    /// Number of frame durations elapsed since the start
+    elapsed: usize,
+    /// Bytes written in the file
+    count: Rc<Cell<usize>>,
+    /// Last frame written, when writing delta frames
+    previous: Option<Vec<u8>>,
+    /// Index of the transparent colour, if the palette has room for it
+    transparent: Option<u8>,
+    shadow: Option<Shadow>,
 }
 
 #[allow(dead_code)]
-impl MyGif<'_> {
-    fn new<'a> (file: &'a mut File, w: u16, h: u16, global_palette: &[u8]) -> MyGif<'a> {
-        let mut encoder = Encoder::new(file, w, h, global_palette).unwrap();
-        encoder.set(Repeat::Infinite).unwrap();
-
+impl MyGif {
+    /// Creates the GIF, played at fps frames per second, loops times (0 meaning
+    /// forever). If delta is set, frames are written as the rectangle that
+    /// changed since the previous one, and also encoded whole to know what it
+    /// saved if compare is set.
+    pub fn new(file: File, fps: f64, loops: usize, delta: bool, compare: bool) -> MyGif {
         MyGif {
-            width: w,           
-            height: h,
-            encoder: encoder,
+            file: Some(file),
+            encoder: None,
+            width: 0,
+            height: 0,
+            fps,
+            loops,
+            delta,
+            compare,
+            elapsed: 0,
+            count: Rc::new(Cell::new(0)),
+            previous: None,
+            transparent: None,
+            shadow: None,
+        }
+    }
+
+    /// Returns the frame that turns previous into t: the smallest rectangle
+    /// containing all the pixels that changed, where those that did not are
+    /// transparent. It is drawn over previous, that is kept.
+    fn delta_frame(&self, previous: &[u8], t: &[u8], delay: u16) -> Frame<'static> {
+        let w = self.width as usize;
+        let (xmin, xmax, ymin, ymax) = match changed_rect(previous, t, w) {
+            Some(rect) => rect,
+            None => {
+                // nothing changed, but the frame is still needed for its delay
+                return Frame {
+                    delay,
+                    dispose: DisposalMethod::Keep,
+                    transparent: self.transparent,
+                    width: 1,
+                    height: 1,
+                    buffer: Cow::Owned(vec![self.transparent.unwrap_or(previous[0])]),
+                    ..Frame::default()
+                }
+            }
+        };
+
+        let mut buffer = Vec::with_capacity((xmax - xmin + 1) * (ymax - ymin + 1));
+        for y in ymin..=ymax {
+            for x in xmin..=xmax {
+                let i = y*w + x;
+                buffer.push(match self.transparent {
+                    Some(tr) if t[i] == previous[i] => tr,
+                    _ => t[i],
+                });
+            }
+        }
+        Frame {
+            delay,
+            dispose: DisposalMethod::Keep,
+            transparent: self.transparent,
+            left:   xmin as u16,
+            top:    ymin as u16,
+            width:  (xmax - xmin + 1) as u16,
+            height: (ymax - ymin + 1) as u16,
+            buffer: Cow::Owned(buffer),
+            ..Frame::default()
+        }
+    }
+}
+
+impl FrameSink for MyGif {
+    fn start(&mut self, width: usize, height: usize, palette: &Palette) -> Result<(), Error> {
+        let global_palette = palette.to_bytes();
+        self.width = width as u16;
+        self.height = height as u16;
+
+        // one more colour, that will be transparent
+        let mut colors = global_palette.clone();
+        if self.delta && colors.len() / 3 < 256 {
+            self.transparent = Some((colors.len() / 3) as u8);
+            colors.extend_from_slice(&global_palette[0..3]);
+        }
+
+        // the first play is not a repetition, and 0 repetitions writes nothing
+        let loops = self.loops;
+        let repeat = || if loops == 0 { Repeat::Infinite }
+                        else { Repeat::Finite((loops - 1).min(u16::MAX as usize) as u16) };
+
+        let file = self.file.take().expect("GIF output started twice");
+        let counter = Counter { inner: file, count: self.count.clone() };
+        let mut encoder = Encoder::new(counter, self.width, self.height, &colors)?;
+        encoder.set(repeat())?;
+        self.encoder = Some(encoder);
+
+        if self.delta && self.compare {
+            let count = Rc::new(Cell::new(0));
+            let counter = Counter { inner: io::sink(), count: count.clone() };
+            let mut encoder = Encoder::new(counter, self.width, self.height, &global_palette)?;
+            encoder.set(repeat())?;
+            self.shadow = Some(Shadow { encoder, count });
         }
+        Ok(())
     }
 
-    fn write_frame(&mut self, t: &[u8]) {
-        let mut frame = Frame::default();
-        frame.width  = self.width;
-        frame.height = self.height;
-        frame.buffer = Cow::Borrowed(&*t);
-        self.encoder.write_frame(&frame).unwrap();
+    /// GIF delays are in hundredths of second: each one is rounded so that
+    /// the rounding errors do not add up over the animation.
+    fn write_frame(&mut self, t: &[u8], n: usize) -> Result<(), Error> {
+        let delay = delay_for(self.elapsed, n, self.fps, 100.0);
+        self.elapsed += n;
+
+        let frame = Frame {
+            width:  self.width,
+            height: self.height,
+            delay:  delay.min(u16::MAX as usize) as u16,
+            buffer: Cow::Borrowed(t),
+            ..Frame::default()
+        };
+
+        if let Some(shadow) = self.shadow.as_mut() {
+            shadow.encoder.write_frame(&frame)?;
+        }
+
+        let delta = self.previous.take().map(|previous| self.delta_frame(&previous, t, frame.delay));
+        let encoder = self.encoder.as_mut().expect("GIF output not started");
+        encoder.write_frame(delta.as_ref().unwrap_or(&frame))?;
+        if self.delta {
+            self.previous = Some(t.to_vec());
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<(usize, Option<usize>), Error> {
+        // the trailer is written when the encoders are dropped
+        self.encoder = None;
+        let full = self.shadow.take().map(|shadow| {
+            let count = shadow.count.clone();
+            drop(shadow);
+            count.get()
+        });
+        Ok((self.count.get(), full))
     }
 }
 
@@ -51,14 +600,14 @@ fn limit(x: usize, y: usize, tabw: usize, tabh: usize)
 fn limit_real(x: f64, y: f64, tabw: usize, tabh: usize) -> (usize, usize) {
     let x2 = if x < 0.0_f64 { 0 } else { x as usize };
     let y2 = if y < 0.0_f64 { 0 } else { y as usize };
-    return limit(x2, y2, tabw, tabh);
+    limit(x2, y2, tabw, tabh)
 }
 
 /* Draws the line (xi, yi) -- (xf, yf) in
  * array tab. */
-#[allow(dead_code)]
-fn draw_line(xi: usize, yi: usize, xf: usize, yf: usize, color: u8,
-             tab: &mut [u8], tabw: usize, tabh: usize) {
+#[allow(dead_code, clippy::too_many_arguments)]
+fn draw_line<C: Canvas>(xi: usize, yi: usize, xf: usize, yf: usize, color: u8,
+             tab: &mut C, tabw: usize, tabh: usize) {
     
     let (xi2, yi2) = limit(xi, yi, tabw, tabh);
     let (xf2, yf2) = limit(xf, yf, tabw, tabh);
@@ -71,13 +620,13 @@ fn draw_line(xi: usize, yi: usize, xf: usize, yf: usize, color: u8,
                                else         { (yi2, yf2, false)  };
     
     if x1 == x2 {
-        if y1 == y2 {   tab[y1*tabw + x1] = color; 
+        if y1 == y2 {   tab.set(y1*tabw + x1, color); 
                         return }
-        for i in y1..=y2 {   tab[i*tabw + x1] = color;   }
+        for i in y1..=y2 {   tab.set(i*tabw + x1, color);   }
         return
     }
     if y1 == y2 {
-        for i in x1..=x2 {   tab[y1*tabw + i] = color;   }
+        for i in x1..=x2 {   tab.set(y1*tabw + i, color);   }
         return
 
     }
@@ -91,25 +640,25 @@ fn draw_line(xi: usize, yi: usize, xf: usize, yf: usize, color: u8,
     if !x_inversed {
         if !y_inversed { // quadrant 4
             for x in x1..x2 {
-                tab[y*tabw + x] = color;
+                tab.set(y*tabw + x, color);
 
                 e += ex;
                 while e >= 0 {
                     y += 1;
                     e += ey;
-                    if e >= 0 {   tab[y*tabw + x] = color;   }
+                    if e >= 0 {   tab.set(y*tabw + x, color);   }
                 }
             }
         }
         else { // quadrant 1 
             for x in x1..x2 {
-                tab[(y1+y2-y)*tabw + x] = color;
+                tab.set((y1+y2-y)*tabw + x, color);
 
                 e += ex;
                 while e >= 0 {
                     y += 1;
                     e += ey;
-                    if e >= 0 {   tab[(y1+y2-y)*tabw + x] = color;   }
+                    if e >= 0 {   tab.set((y1+y2-y)*tabw + x, color);   }
                 }
             }
         }
@@ -117,25 +666,25 @@ fn draw_line(xi: usize, yi: usize, xf: usize, yf: usize, color: u8,
     else { 
         if !y_inversed { // quadrant 3
             for x in x1..x2 {
-                tab[y*tabw + x1+x2-x] = color;
+                tab.set(y*tabw + x1+x2-x, color);
 
                 e += ex;
                 while e >= 0 {
                     y += 1;
                     e += ey;
-                    if e >= 0 {   tab[y*tabw + x1+x2-x] = color;   }
+                    if e >= 0 {   tab.set(y*tabw + x1+x2-x, color);   }
                 }
             }
         }
         else { // quadrant 2
             for x in x1..x2 {
-                tab[(y1+y2-y)*tabw + x1+x2-x] = color;
+                tab.set((y1+y2-y)*tabw + x1+x2-x, color);
 
                 e += ex;
                 while e >= 0 {
                     y += 1;
                     e += ey;
-                    if e >= 0 {   tab[(y1+y2-y)*tabw + x1+x2-x] = color;   }
+                    if e >= 0 {   tab.set((y1+y2-y)*tabw + x1+x2-x, color);   }
                 }
             }
         }
@@ -143,106 +692,454 @@ fn draw_line(xi: usize, yi: usize, xf: usize, yf: usize, color: u8,
 
 }
 
-fn draw_dot(x: usize, y: usize, color: u8,
-    tab: &mut [u8], tabw: usize, tabh: usize) {
+fn draw_dot<C: Canvas>(x: usize, y: usize, color: u8,
+    tab: &mut C, tabw: usize, tabh: usize) {
 
     let (x2, y2) = limit(x, y, tabw, tabh);
-    tab[y2*tabw + x2] = color;
+    tab.set(y2*tabw + x2, color);
+}
+
+/* Draws the circle of center (xc, yc) and radius r in array tab, using the
+ * midpoint algorithm. Pixels falling outside of tab are simply skipped, as
+ * the center may lie far away from the visible area. Circles that miss tab
+ * are not drawn, and those much bigger than it are drawn by draw_big_circle,
+ * so that the time spent does not grow with the radius. */
+fn draw_circle<C: Canvas>(xc: i64, yc: i64, r: i64, color: u8,
+    tab: &mut C, tabw: usize, tabh: usize) {
+
+    // distances from the center to the nearest and farthest points of tab
+    let (w, h) = (tabw as f64, tabh as f64);
+    let (xcf, ycf) = (xc as f64, yc as f64);
+    let near = (xcf.clamp(0.0, w) - xcf).hypot(ycf.clamp(0.0, h) - ycf);
+    let far = xcf.abs().max((xcf - w).abs()).hypot(ycf.abs().max((ycf - h).abs()));
+    if (r as f64) < near - 1.0 || (r as f64) > far + 1.0 {
+        return
+    }
+    if r as f64 > 2.0 * (w + h) {
+        return draw_big_circle(xcf, ycf, r as f64, color, tab, tabw, tabh)
+    }
+
+    let mut plot = |x: i64, y: i64| {
+        if x >= 0 && y >= 0 && (x as usize) < tabw && (y as usize) < tabh {
+            tab.set(y as usize * tabw + x as usize, color);
+        }
+    };
+
+    let mut x = r;
+    let mut y = 0;
+    let mut e = 1 - r;
+    while x >= y {
+        // one point per octant
+        plot(xc + x, yc + y);   plot(xc + y, yc + x);
+        plot(xc - y, yc + x);   plot(xc - x, yc + y);
+        plot(xc - x, yc - y);   plot(xc - y, yc - x);
+        plot(xc + y, yc - x);   plot(xc + x, yc - y);
+
+        y += 1;
+        if e < 0 {
+            e += 2*y + 1;
+        } else {
+            x -= 1;
+            e += 2*(y - x) + 1;
+        }
+    }
+}
+
+/* Draws the circle of center (xc, yc) and radius r in array tab, going
+ * through its rows and columns instead of the circle: each row holds the
+ * points of the circle where it is steep, each column the others. */
+fn draw_big_circle<C: Canvas>(xc: f64, yc: f64, r: f64, color: u8,
+    tab: &mut C, tabw: usize, tabh: usize) {
+
+    // the other coordinate of the points of the circle at distance d of the
+    // center along one axis, if the circle is steep enough there
+    let across = |d: f64| {
+        let a = (r*r - d*d).max(0.0).sqrt();
+        if d.abs() <= r && a >= d.abs() { Some(a) } else { None }
+    };
+    for y in 0..tabh {
+        if let Some(a) = across(y as f64 - yc) {
+            for x in [(xc - a).round(), (xc + a).round()] {
+                if x >= 0.0 && x < tabw as f64 {   tab.set(y*tabw + x as usize, color);   }
+            }
+        }
+    }
+    for x in 0..tabw {
+        if let Some(a) = across(x as f64 - xc) {
+            for y in [(yc - a).round(), (yc + a).round()] {
+                if y >= 0.0 && y < tabh as f64 {   tab.set(y as usize * tabw + x, color);   }
+            }
+        }
+    }
 }
 
 /* see https://fr.wikipedia.org/wiki/Algorithme_de_trac%C3%A9_de_segment_de_Bresenham */
 
-/// Draws in filename gif the figure represented by
+/* Draws the segment (x0, y0) -- (x1, y1) with the given width in array tab,
+ * as the set of pixels whose center is close enough to it. Each pixel gets a
+ * coverage in [0, 1] from its distance to the segment, with which it is
+ * painted when anti-aliasing, and that is thresholded at 1/2 otherwise. A
+ * segment of null length draws a disc. */
+#[allow(clippy::too_many_arguments)]
+fn draw_stroke<C: Canvas>(p0: (f64, f64), p1: (f64, f64), width: f64, color: u8,
+    antialias: bool, palette: &Palette, tab: &mut C, tabw: usize, tabh: usize) {
+
+    if !(p0.0.is_finite() && p0.1.is_finite() && p1.0.is_finite() && p1.1.is_finite()) {
+        return
+    }
+    let r = width / 2.0;
+    let clamp = |v: f64, max: usize| v.max(0.0).min(max as f64) as usize;
+    let (xmin, xmax) = (clamp((p0.0.min(p1.0) - r - 1.0).floor(), tabw),
+                        clamp((p0.0.max(p1.0) + r + 1.0).ceil(),  tabw));
+    let (ymin, ymax) = (clamp((p0.1.min(p1.1) - r - 1.0).floor(), tabh),
+                        clamp((p0.1.max(p1.1) + r + 1.0).ceil(),  tabh));
+
+    let (dx, dy) = (p1.0 - p0.0, p1.1 - p0.1);
+    let len2 = dx*dx + dy*dy;
+    for y in ymin..ymax {
+        for x in xmin..xmax {
+            let (px, py) = (x as f64 + 0.5, y as f64 + 0.5);
+            // projection of the pixel's center on the segment
+            let u = if len2 > 0.0 { (((px - p0.0)*dx + (py - p0.1)*dy) / len2).clamp(0.0, 1.0) }
+                    else          { 0.0 };
+            let d = (px - p0.0 - u*dx).hypot(py - p0.1 - u*dy);
+            let coverage = (r + 0.5 - d).clamp(0.0, 1.0);
+
+            let coverage = if antialias { coverage }
+                           else if coverage >= 0.5 { 1.0 }
+                           else { 0.0 };
+            if coverage > 0.0 {
+                tab.paint(y*tabw + x, color, coverage, palette);
+            }
+        }
+    }
+}
+
+/* Draws the segment (x0, y0) -- (x1, y1) as required by opts: with the 1 pixel
+ * wide Bresenham's algorithm if possible, with draw_stroke otherwise. */
+#[allow(clippy::too_many_arguments)]
+fn draw_segment<C: Canvas>(p0: (f64, f64), p1: (f64, f64), width: f64, color: u8,
+    opts: &DrawOptions, palette: &Palette, tab: &mut C) {
+
+    let (w, h) = (opts.width, opts.height);
+    if opts.antialias || width > 1.0 {
+        draw_stroke(p0, p1, width, color, opts.antialias, palette, tab, w, h);
+    } else {
+        let (x0, y0) = limit_real(p0.0, p0.1, w, h);
+        let (x1, y1) = limit_real(p1.0, p1.1, w, h);
+        draw_line(x0, y0, x1, y1, color, tab, w, h);
+    }
+}
+
+/// Returns the inks (arm, circle) of each term, as required by scheme. The
+/// colours of the Frequency scheme are added to palette, as many hues as it can
+/// hold: neighbouring frequencies share the same one if needed.
+pub fn term_inks(terms: &[(i32, Complex)], scheme: ColorScheme, palette: &mut Palette)
+    -> Vec<(u8, u8)> {
+
+    let max_k = terms.iter().map(|&(k, _)| k.unsigned_abs() as usize).max().unwrap_or(0);
+    let n_hues = max_k.min(palette.room() / 2);
+    if scheme == ColorScheme::Mono || n_hues == 0 {
+        return vec![(ARMS, CIRCLES); terms.len()]
+    }
+
+    let mut hues = Vec::with_capacity(n_hues);
+    for i in 0..n_hues {
+        let arm = color::from_hsv(300.0 * i as f64 / n_hues.max(2).saturating_sub(1) as f64,
+            0.85, 0.85);
+        let circle = color::mix(arm, palette.background, 0.5);
+        hues.push((palette.add_ink(arm), palette.add_ink(circle)));
+    }
+    terms.iter()
+         .map(|&(k, _)| hues[(k.unsigned_abs() as usize - 1) * n_hues / max_k])
+         .collect()
+}
+
+/* Draws the traced drawing through the pixel positions tips, the last one
+ * being the newest. If fade is given as (first ink, number of inks), the
+ * oldest segments are drawn with those inks, that go to the background, by
+ * their age relative to the length of the trail, `opts.trail`: a segment
+ * keeps its ink while the trail fills up. */
+fn draw_trail<C: Canvas>(tips: &[(f64, f64)], fade: Option<(u8, usize)>,
+    opts: &DrawOptions, palette: &Palette, tab: &mut C) {
+
+    if tips.len() == 1 {
+        draw_trace_dot(tips[0], opts, palette, tab);
+    }
+    let n = tips.len().saturating_sub(1);
+    // oldest first, so that the newest ones are on top
+    for i in 0..n {
+        let color = match fade {
+            None => TRACE,
+            Some((first, shades)) => {
+                let age = n - 1 - i;
+                let length = opts.trail.unwrap_or(n).max(n);
+                match age * (shades + 1) / length {
+                    0 => TRACE,
+                    f => first + f as u8 - 1,
+                }
+            }
+        };
+        draw_segment(tips[i], tips[i+1], opts.trace_width, color, opts, palette, tab);
+    }
+}
+
+/* Draws one point of the traced drawing, at pixel position p. */
+fn draw_trace_dot<C: Canvas>(p: (f64, f64), opts: &DrawOptions, palette: &Palette,
+    tab: &mut C) {
+    let (w, h) = (opts.width, opts.height);
+    if opts.antialias || opts.trace_width > 1.0 {
+        draw_stroke(p, p, opts.trace_width, TRACE, opts.antialias, palette, tab, w, h);
+    } else {
+        let (x, y) = limit_real(p.0, p.1, w, h);
+        draw_dot(x, y, TRACE, tab, w, h);
+    }
+}
+
+/* Draws a marker of the given color at pixel position p: a disc a bit wider
+ * than the strokes, so that it stands out. */
+fn draw_marker<C: Canvas>(p: (f64, f64), color: u8, opts: &DrawOptions, palette: &Palette,
+    tab: &mut C) {
+    let width = opts.trace_width.max(opts.arm_width) + 2.0;
+    draw_stroke(p, p, width, color, opts.antialias, palette, tab, opts.width, opts.height);
+}
+
+/// Returns the points of the chain of epicycles at time t: the anchor, then
+/// the tip of each arm. The last one is thus the traced point.
+pub fn chain(anchor: Complex, terms: &[(i32, Complex)], omega0: f64, t: f64)
+    -> Vec<Complex> {
+    let mut points = Vec::with_capacity(terms.len() + 1);
+    let mut p = anchor;
+    points.push(p);
+    for &(k, c) in terms.iter() {
+        //   c_k * e^{j k omega0 t}
+        p += c * Complex::expj(k as f64 * omega0 * t);
+        points.push(p);
+    }
+    points
+}
+
+/// Returns the bounding box of the epicycles at the given times, circles
+/// included if asked, and of marks: the whole animation has to fit.
+pub fn epicycles_bbox(anchor: Complex, terms: &[(i32, Complex)], omega0: f64, times: &[f64],
+    marks: &[Complex], circles: bool) -> BBox {
+    let mut bbox = BBox::new();
+    for &t in times.iter() {
+        let points = chain(anchor, terms, omega0, t);
+        for (i, p) in points.iter().enumerate() {
+            bbox.extend(*p);
+            if circles && i < terms.len() {
+                bbox.extend_circle(*p, terms[i].1.modulus());
+            }
+        }
+    }
+    for m in marks.iter() {
+        bbox.extend(*m);
+    }
+    bbox
+}
+
+/// Draws in output the animation of the figure represented by
 /// the Fourier coefficients in coeffs.
-pub fn draw_fourier_coeff(coeffs: CoeffsSet, filename: &str, w: usize, h: usize,
-    t_span: (f64, f64), n_steps: usize, global_palette: &[u8]) -> Result<(), Error> {
+/// If `opts.circles` is set, the circle described by each term is drawn too,
+/// centered on the tip of the previous arm. The arms are chained following
+/// `opts.order`, starting from the point given by `opts.anchor`.
+/// If `opts.show_points` is set, marks are drawn on the original control
+/// points, which are given in marks.
+/// The returned report holds the offset that was applied to the drawing, i.e.
+/// `-c_0` when it has been recentered, and 0 otherwise.
+/// Frames are drawn in colours if output takes them, as palette indices
+/// otherwise.
+pub fn draw_fourier_coeff(coeffs: CoeffsSet, marks: &[Complex], output: &mut dyn FrameSink,
+    t_span: (f64, f64), opts: &DrawOptions, palette: &Palette) -> Result<DrawReport, Error> {
+    if output.true_color() {
+        animate::<RgbFrame>(coeffs, marks, output, t_span, opts, palette)
+    } else {
+        animate::<IndexFrame>(coeffs, marks, output, t_span, opts, palette)
+    }
+}
 
-    // gotest(20, 20, 200, 200);
-    let n = coeffs.ppos.len();
-    assert_eq!(n, coeffs.nneg.len());
+/* Draws the animation of `draw_fourier_coeff` in frames of type C. */
+fn animate<C: Canvas>(coeffs: CoeffsSet, marks: &[Complex], output: &mut dyn FrameSink,
+    t_span: (f64, f64), opts: &DrawOptions, palette: &Palette) -> Result<DrawReport, Error> {
+
+    let (w, h, n_steps) = (opts.width, opts.height, opts.n_steps);
+    assert_eq!(coeffs.ppos.len(), coeffs.nneg.len());
+    let terms = ordered_terms(&coeffs, opts.order);
+    let (anchor, offset) = match opts.anchor {
+        Anchor::Dc     => (coeffs.dc(),     Complex::zero()),
+        Anchor::Center => (Complex::zero(), Complex::zero() - coeffs.dc()),
+    };
+    let marks: Vec<Complex> = if opts.show_points { marks.iter().map(|&m| m + offset).collect() }
+                              else                { Vec::new() };
 
-    let mut output = File::create(filename)?;
-    let mut gif = MyGif::new(&mut output, w as u16, h as u16, global_palette);
-    
-    let vect = vec![0; (w*h) as usize];
-    let mut tab_drawing: Box<[u8]> = vect.into_boxed_slice();
-    
-    let mut t: f64 = t_span.0;
-    let max = t_span.1;
     let period = t_span.1 - t_span.0;
     let omega0 = 2.0 * PI / period;
-    while t < max {
+    let times: Vec<f64> = (0..n_steps)
+        .map(|i| t_span.0 + period * i as f64 / n_steps as f64)
+        .collect();
+
+    let view = Viewport::new(&epicycles_bbox(anchor, &terms, omega0, &times, &marks,
+        opts.circles), opts);
+
+    // a fading trail needs its own shades, going to the background
+    let mut palette = palette.clone();
+    let fade = match opts.trail {
+        Some(_) => palette.add_fade(TRACE, TRAIL_SHADES),
+        None    => (TRACE, 0),
+    };
+    let inks = term_inks(&terms, opts.scheme, &mut palette);
+    let palette = &palette;
+
+    output.start(w, h, palette)?;
+    
+    // what never changes: the control points
+    let mut tab_static = C::blank(w, h);
+    for m in marks.iter() {
+        draw_marker(view.to_pixel(*m), POINTS, opts, palette, &mut tab_static);
+    }
+
+    let mut tab_drawing = tab_static.clone();
+    let mut tips: Vec<(f64, f64)> = Vec::with_capacity(times.len());
+    
+    for (step, &t) in times.iter().enumerate() {
+        let points = chain(anchor, &terms, omega0, t);
+        tips.push(view.to_pixel(points[terms.len()]));
+
+        match opts.trail {
+            None => draw_trail(&tips[tips.len().saturating_sub(2)..], None,
+                opts, palette, &mut tab_drawing),
+            Some(n) => {
+                // redrawn from scratch, as every part of it gets older
+                tab_drawing.clone_from(&tab_static);
+                draw_trail(&tips[tips.len().saturating_sub(n + 1)..], Some(fade),
+                    opts, palette, &mut tab_drawing);
+            }
+        }
+
         // keep what's already drawed
         let mut tab_lines = tab_drawing.clone();
         
-        let mut x1: f64 = (w as f64) / 2.0;
-        let mut y1: f64 = (h as f64) / 2.0;
-
-        let mut x1_usize: usize = x1 as usize;
-        let mut y1_usize: usize = y1 as usize;
-
-        let omega0_t = omega0 * t;
-
-        let mut k_f64: f64 = 1.0;
-        for k in 1..n {
-            let coeff_pn = (coeffs.ppos[k], coeffs.nneg[k]);
-            for (c, neg) in vec![(coeff_pn.0, false),
-                                 (coeff_pn.1, true) ] {
-                
-                let sin1 = if neg {  -(k_f64 * omega0_t).sin()  }
-                           else   {   (k_f64 * omega0_t).sin()  };
-                let cos1 = (k_f64 * omega0_t).cos();
-                //   (a+ib)*(cos + i sin)
-                // = a cos - b sin + i (a sin + b cos)
-                let x2 = x1 + (c.re*cos1 - c.im*sin1);
-                let y2 = y1 - (c.re*sin1 + c.im*cos1);
-                // Y axis is multiplied by -1 to make the circle drawed anticlockwise 
-
-                let (x2_usize, y2_usize) = limit_real(x2, y2, w, h);
-                draw_line(x1_usize, y1_usize, x2_usize, y2_usize,
-                    1, &mut *tab_lines, w, h);
-                
-                x1 = x2;
-                y1 = y2;
-                x1_usize = x2_usize;
-                y1_usize = y2_usize;
+        for (i, &(_, c)) in terms.iter().enumerate() {
+            let (x1, y1) = view.to_pixel(points[i]);
+            let (x2, y2) = view.to_pixel(points[i+1]);
+
+            if opts.circles {
+                let r = (c.modulus() * view.scale).round() as i64;
+                if r >= 1 {
+                    draw_circle(x1.round() as i64, y1.round() as i64, r,
+                        inks[i].1, &mut tab_lines, w, h);
+                }
             }
 
-            k_f64 += 1.0;
+            draw_segment((x1, y1), (x2, y2), opts.arm_width, inks[i].0,
+                opts, palette, &mut tab_lines);
         }
-        let (xx, yy) = limit_real(x1, y1, w, h);
-        draw_dot(xx, yy, 1, &mut *tab_drawing, w, h);
+        draw_marker(tips[tips.len() - 1], TIP, opts, palette, &mut tab_lines);
 
-        gif.write_frame(&mut *tab_lines);
-        t += period / n_steps as f64;
+        let hold = if step + 1 == times.len() { 1 + opts.pause } else { 1 };
+        tab_lines.write(output, hold, palette)?;
     };
 
-    Ok(())
+    let (bytes, full_bytes) = output.finish()?;
+    Ok(DrawReport { offset, bytes, full_bytes })
 }
 
+/// Draws in output the spline (sx(t), sy(t)) as a single frame, and marks on
+/// the control points marks if `opts.show_points` is set, in colours if output
+/// takes them, as `draw_fourier_coeff` does.
 #[allow(dead_code)]
-pub fn draw_spline(sx: Spline, sy: Spline, filename: &str, w: usize, h: usize,
-     n:usize, global_palette: &[u8]) -> Result<(), Error> {
+pub fn draw_spline(sx: Spline, sy: Spline, marks: &[Complex], output: &mut dyn FrameSink,
+     opts: &DrawOptions, palette: &Palette) -> Result<DrawReport, Error> {
+    if output.true_color() {
+        draw_spline_in::<RgbFrame>(sx, sy, marks, output, opts, palette)
+    } else {
+        draw_spline_in::<IndexFrame>(sx, sy, marks, output, opts, palette)
+    }
+}
 
-    let mut output = File::create(filename)?;
-    let mut gif = MyGif::new(&mut output, w as u16, h as u16, global_palette);
+/* Draws the frame of `draw_spline` as a frame of type C. */
+fn draw_spline_in<C: Canvas>(sx: Spline, sy: Spline, marks: &[Complex],
+    output: &mut dyn FrameSink, opts: &DrawOptions, palette: &Palette)
+    -> Result<DrawReport, Error> {
 
-    let vect = vec![0; (w*h) as usize];
-    let mut tab: Box<[u8]> = vect.into_boxed_slice();
+    let (w, h, n) = (opts.width, opts.height, opts.n_steps);
+    let period = sx.end() - sx.start();
+    let points: Vec<Complex> = (0..n)
+        .map(|i| sx.start() + period * i as f64 / n as f64)
+        .map(|t| Complex { re: sx.eval(t), im: sy.eval(t) })
+        .collect();
 
+    let marks = if opts.show_points { marks } else { &[] };
 
-    let period = sx.end() - sx.start();
-    let mut t: f64 = 0.0;
-    let cx = w / 2;
-    let cy = h / 2;
+    let mut bbox = BBox::new();
+    for p in points.iter().chain(marks.iter()) {
+        bbox.extend(*p);
+    }
+    let view = Viewport::new(&bbox, opts);
+
+    output.start(w, h, palette)?;
 
-    while t < period {
-        let (dx, dy) = (sx.eval(t), sy.eval(t));
-        let (x, y) = (cx as f64 + dx, cy as f64 - dy);
-        draw_dot(x as usize, y as usize, 1, &mut *tab, w, h);
-        t += period / n as f64;
+    let mut tab = C::blank(w, h);
+
+    for m in marks.iter() {
+        draw_marker(view.to_pixel(*m), POINTS, opts, palette, &mut tab);
+    }
+    for p in points {
+        draw_trace_dot(view.to_pixel(p), opts, palette, &mut tab);
     }
-    gif.write_frame(&mut *tab);
-    Ok(())
+    tab.write(output, 1, palette)?;
+
+    let (bytes, full_bytes) = output.finish()?;
+    Ok(DrawReport { offset: Complex::zero(), bytes, full_bytes })
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn palette() -> Palette {
+        Palette::new((255, 255, 255), vec![(0, 0, 0), (255, 0, 0)], 1, false)
+    }
+
+    #[test]
+    fn rgb_frame_blends_inks_by_their_coverage() {
+        let palette = palette();
+        let mut frame = RgbFrame::blank(3, 1);
+        frame.paint(0, ARMS, 0.5, &palette);
+        frame.paint(1, ARMS, 0.25, &palette);
+        frame.paint(1, ARMS, 0.75, &palette);
+        frame.paint(1, ARMS, 0.5, &palette);
+        frame.paint(2, ARMS, 1.0, &palette);
+        frame.paint(2, CIRCLES, 0.5, &palette);
+        let colors: Vec<Rgb> = frame.0.iter().map(|l| l.color(&palette)).collect();
+        assert_eq!(colors, vec![(128, 128, 128), (64, 64, 64), (128, 0, 0)]);
+    }
+
+    #[test]
+    fn rgb_frame_set_covers_what_is_under() {
+        let palette = palette();
+        let mut frame = RgbFrame::blank(1, 1);
+        frame.paint(0, ARMS, 0.5, &palette);
+        Canvas::set(&mut frame, 0, CIRCLES);
+        assert_eq!(frame.0[0].color(&palette), (255, 0, 0));
+    }
+
+    #[test]
+    fn huge_circles_are_drawn_where_they_cross_the_frame() {
+        let (w, h) = (30, 20);
+        let r = 1_000_000_000_000;
+        let mut frame = IndexFrame::blank(w, h);
+        draw_circle(10 - r, 5, r, CIRCLES, &mut frame, w, h);
+        for y in 0..h {
+            let row = &frame.0[y*w..(y + 1)*w];
+            assert_eq!(row.iter().filter(|&&c| c == CIRCLES).count(), 1);
+            assert_eq!(row[10], CIRCLES);
+        }
+
+        let mut frame = IndexFrame::blank(w, h);
+        draw_circle(0, 0, r, CIRCLES, &mut frame, w, h);
+        draw_circle(-r, -r, r, CIRCLES, &mut frame, w, h);
+        assert!(frame.0.iter().all(|&c| c == BACKGROUND));
+    }
+}
@@ -0,0 +1,109 @@
+use std::f64::consts::PI;
+
+use super::fourier::CoeffsSet;
+use super::complex::Complex;
+use super::spline::Spline;
+use super::color::Rgb;
+use super::fgif::*;
+
+/// A still drawing, in pixels, as written by the vector formats.
+/// Members:
+///
+/// - `width`, `height` : size of the drawing
+/// - `background`      : colour of the background
+/// - `items`           : what is drawn, the first ones below
+pub struct Figure {
+    pub width: usize,
+    pub height: usize,
+    pub background: Rgb,
+    pub items: Vec<Item>,
+}
+
+/// Something drawn on a `Figure`, at pixel positions.
+pub enum Item {
+    /// Chained cubic Bézier curves: the first point, then the two control
+    /// points and the end of each curve
+    Curve { start: (f64, f64), curves: Vec<[(f64, f64); 3]>, color: Rgb, width: f64 },
+    /// Segments joining the points, one after the other
+    Polyline { points: Vec<(f64, f64)>, color: Rgb, width: f64 },
+    /// A filled disc
+    Disc { center: (f64, f64), radius: f64, color: Rgb },
+}
+
+/// Builds the still drawing of the spline (sx(t), sy(t)), exactly, as Bézier
+/// curves, in the `TRACE` ink, and of the reconstruction given by the Fourier
+/// coefficients over the time span, sampled at `opts.n_steps` points, in the
+/// `CIRCLES` ink. Both are optional, so that they can be compared or drawn
+/// alone. The control points marks are marked if `opts.show_points` is set.
+/// As the drawing is meant to compare the curves, they are never recentered.
+pub fn still(spline: Option<(&Spline, &Spline)>, fourier: Option<(&CoeffsSet, (f64, f64))>,
+    marks: &[Complex], opts: &DrawOptions, palette: &Palette) -> Figure {
+
+    let n = opts.n_steps.max(1);
+    let mut bbox = BBox::new();
+
+    let curves = spline.map(|(sx, sy)| {
+        let (bx, by) = (sx.beziers(), sy.beziers());
+        let control: Vec<[Complex; 4]> = bx.iter().zip(by.iter())
+            .map(|(x, y)| [0, 1, 2, 3].map(|i| Complex { re: x[i], im: y[i] }))
+            .collect();
+        // the curves stay within their control points
+        for c in control.iter() {
+            for p in c.iter() {
+                bbox.extend(*p);
+            }
+        }
+        control
+    });
+
+    let reconstruction = fourier.map(|(coeffs, t_span)| {
+        let terms = coeffs.terms();
+        let period = t_span.1 - t_span.0;
+        let omega0 = 2.0 * PI / period;
+        let points: Vec<Complex> = (0..=n)
+            .map(|i| t_span.0 + period * i as f64 / n as f64)
+            .map(|t| chain(coeffs.dc(), &terms, omega0, t)[terms.len()])
+            .collect();
+        for p in points.iter() {
+            bbox.extend(*p);
+        }
+        points
+    });
+
+    let marks = if opts.show_points { marks } else { &[] };
+    for m in marks.iter() {
+        bbox.extend(*m);
+    }
+    let view = Viewport::new(&bbox, opts);
+
+    let mut items = Vec::new();
+    if let Some(points) = reconstruction {
+        items.push(Item::Polyline {
+            points: points.iter().map(|&p| view.to_pixel(p)).collect(),
+            color: palette.color(CIRCLES),
+            width: opts.trace_width,
+        });
+    }
+    if let Some(control) = curves {
+        if !control.is_empty() {
+            items.push(Item::Curve {
+                start: view.to_pixel(control[0][0]),
+                curves: control.iter()
+                    .map(|c| [view.to_pixel(c[1]), view.to_pixel(c[2]), view.to_pixel(c[3])])
+                    .collect(),
+                color: palette.color(TRACE),
+                width: opts.trace_width,
+            });
+        }
+    }
+    for m in marks.iter() {
+        items.push(Item::Disc {
+            center: view.to_pixel(*m),
+            radius: (opts.trace_width.max(opts.arm_width) + 2.0) / 2.0,
+            color: palette.color(POINTS),
+        });
+    }
+
+    Figure { width: opts.width, height: opts.height, background: palette.color(BACKGROUND),
+             items }
+}
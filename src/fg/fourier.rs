@@ -22,21 +22,44 @@ pub fn compute_fourier_coeffs(sx: & Spline, sy: & Spline, n: usize) -> CoeffsSet
     let omega_0_na = 2.0 * PI / period;
     let constants = Constants {
         omega_0_inv: FourTerms::new_pwr(1.0 / omega_0_na),
-        changes: sx.changes()
     };
 
     let mut coeffs = CoeffsSet::new(n);
 
-    coeffs.ppos[0] = Complex::zero();
+    coeffs.ppos[0] = compute_dc(sx, sy);
     coeffs.nneg[0] = Complex::zero();
     for k in 1..n {
-        coeffs.ppos[k] = compute_one( k as i32,   & sx, & sy, & constants);
-        coeffs.nneg[k] = compute_one(-(k as i32), & sx, & sy, & constants);
+        coeffs.ppos[k] = compute_one( k as i32,   sx, sy, & constants);
+        coeffs.nneg[k] = compute_one(-(k as i32), sx, sy, & constants);
     }
 
     coeffs
 }
 
+/// Computes the DC term c_0 of sx(t) + j * sy(t), i.e. its mean value over
+/// the period, which is the centroid of the path.
+/// Output:  1/T * \int_T (sx(t) + j * sy(t)) dt
+fn compute_dc(sx: & Spline, sy: & Spline) -> Complex {
+    Complex {
+        re: integral(sx),
+        im: integral(sy)
+    } / (sx.end() - sx.start())
+}
+
+/// Computes the integral of the spline s over all its parts, each from u = 0
+/// to its length.
+fn integral(s: & Spline) -> f64 {
+    let changes = s.changes();
+    let prim = |sp: SplinePart, u: f64|
+        ((sp.a / 4.0 * u + sp.b / 3.0) * u + sp.c / 2.0) * u * u + sp.d * u;
+
+    let mut sum = 0.0;
+    for p in 0..s.num_parts() {
+        sum += prim(s.part(p), changes[p+1] - changes[p]);
+    }
+    sum
+}
+
 /// Computes the k-th fourier coefficient of sx(t) + j * sy(t). 
 /// Achieves the sum over all the spline parts.
 /// Output:  1/T * \hat f_k
@@ -51,42 +74,43 @@ fn compute_one(k: i32, sx: & Spline, sy: & Spline, constants:&  Constants)
     let mut x_k = Complex::zero();
     let mut y_k = Complex::zero();
 
-    let mut t_i: ThreeTerms;
-    let mut t_f: ThreeTerms = ThreeTerms::new_pwr(sx.start());
+    let changes = sx.changes();
     for p in 0..sx.num_parts() {
-        t_i = t_f;
-        t_f = ThreeTerms::new_pwr(sx.changes()[p+1]);
-        x_k += part_contribution(sx, p, & r, & t_i, & t_f);
-        y_k += part_contribution(sy, p, & r, & t_i, & t_f);
+        let (t_i, t_f) = (changes[p], changes[p+1]);
+        x_k += part_contribution(sx, p, & r, t_i, t_f);
+        y_k += part_contribution(sy, p, & r, t_i, t_f);
     }
 
     (x_k + y_k.times_j()) / (sx.end() - sx.start())
 }
 
 /// Computes the contribution of the p-th part of the spline to the fourier
-/// coefficient (of function s(t)) value, between t_i and t_f.
+/// coefficient (of function s(t)) value, between t_i and t_f, i.e. from
+/// u = 0 to u = t_f - t_i.
 /// Output: \hat x_{k, p}
 fn part_contribution(s: & Spline, p: usize, r: & FourTerms,
-    t_i: & ThreeTerms, t_f: & ThreeTerms) -> Complex {
+    t_i: f64, t_f: f64) -> Complex {
 
     let part = s.part(p);
     
-    primitive(part, & t_f, & r) -  
-    primitive(part, & t_i, & r)
+    primitive(part, & ThreeTerms::new_pwr(t_f - t_i), t_f, r) -  
+    primitive(part, & ThreeTerms::new_pwr(0.0), t_i, r)
 }
 
-/// Computes the value of the primitive (there is only one primitive...).
+/// Computes the value of the primitive (there is only one primitive...), at
+/// u from the start of the part, i.e. at time t. The polynomial and its
+/// derivatives only take the powers of u, which stay small.
 /// Output: F(k, a, b, c, d, t)
-fn primitive(sp: SplinePart, t: & ThreeTerms, r: & FourTerms) -> Complex {
-    let term_1 = r.na * (sp.a * t.cu + sp.b * t.sq + sp.c * t.na + sp.d);
-    let term_2 = r.sq * (3.0 * sp.a * t.sq + 2.0 * sp.b * t.na + sp.c);
-    let term_3 = r.cu * (6.0 * sp.a * t.na + 2.0 * sp.b);
+fn primitive(sp: SplinePart, u: & ThreeTerms, t: f64, r: & FourTerms) -> Complex {
+    let term_1 = r.na * (sp.a * u.cu + sp.b * u.sq + sp.c * u.na + sp.d);
+    let term_2 = r.sq * (3.0 * sp.a * u.sq + 2.0 * sp.b * u.na + sp.c);
+    let term_3 = r.cu * (6.0 * sp.a * u.na + 2.0 * sp.b);
     let term_4 = r.fo * (6.0 * sp.a);
 
     Complex {
         re: term_2 - term_4,
         im: term_1 - term_3
-    } * Complex::expj(-t.na / r.na)
+    } * Complex::expj(-t / r.na)
 }
 
 /// Holds 4 terms, which are powers of the na member 
@@ -100,11 +124,11 @@ struct FourTerms {
 
 impl FourTerms {
     fn new (na: f64, sq: f64, cu: f64, fo: f64) -> Self {
-        Self {  na: na,     sq: sq,
-                cu: cu,     fo: fo                  }
+        Self {  na,  sq,
+                cu,  fo  }
     }
     fn new_pwr (na: f64) -> Self {
-        Self {  na: na,            sq: na.powi(2),
+        Self {  na,                sq: na.powi(2),
                 cu: na.powi(3),    fo: na.powi(4)   }
     }
 }
@@ -118,11 +142,11 @@ struct ThreeTerms {
 #[allow(dead_code)]
 impl ThreeTerms {
     fn new (na: f64, sq: f64, cu: f64) -> Self {
-        Self {  na: na,     sq: sq,
-                cu: cu,          }
+        Self {  na,  sq,
+                cu  }
     }
     fn new_pwr (na: f64) -> Self {
-        Self {  na: na,         sq: na.powi(2),
+        Self {  na,             sq: na.powi(2),
                 cu: na.powi(3)   }
     }
 }
@@ -131,10 +155,11 @@ impl ThreeTerms {
 #[derive(Debug)]
 struct Constants {
     omega_0_inv: FourTerms,
-    changes: Vec<f64>
 }
 
 /// Holds a set of Fourier coefficients. 
+/// `ppos[k]` holds c_k and `nneg[k]` holds c_-k. The DC term c_0 is stored in
+/// `ppos[0]`, `nneg[0]` is unused.
 #[derive(Debug)]
 pub struct CoeffsSet {
     pub ppos: Vec<Complex>,
@@ -149,14 +174,71 @@ impl CoeffsSet {
             nneg: vec![Complex::zero(); n],
         }
     }
+
+    /// Returns the DC term c_0, i.e. the centroid of the drawing.
+    pub fn dc(&self) -> Complex {
+        self.ppos.first().copied().unwrap_or(Complex::zero())
+    }
+
+    /// Returns the terms (k, c_k) of the series, without c_0, in the order
+    /// they are stored: +1, -1, +2, -2, ...
+    pub fn terms(&self) -> Vec<(i32, Complex)> {
+        let mut terms = Vec::new();
+        for k in 1..self.ppos.len() {
+            terms.push(( k as i32, self.ppos[k]));
+            terms.push((-(k as i32), self.nneg[k]));
+        }
+        terms
+    }
 }
 
+/// Writes the coefficients one harmonic per line, as
+/// `k: (Re(c_k), Im(c_k))&(Re(c_-k), Im(c_-k))`, which `read` parses back.
+/// The numbers have as many digits as needed to be read back exactly. The
+/// harmonics that are 0 are left out, but the last one, that gives the size
+/// of the set.
 impl fmt::Display for CoeffsSet {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let mut s = String::new();
-        for i in 0..self.ppos.len() {
-            s.push_str(&format!("{}:\t+{:?}\n\t-{:?}\n", i, self.ppos[i], self.nneg[i]));
+        let n = self.ppos.len();
+        for (k, (cp, cn)) in self.ppos.iter().zip(self.nneg.iter()).enumerate() {
+            let zero = cp.re == 0.0 && cp.im == 0.0 && cn.re == 0.0 && cn.im == 0.0;
+            if zero && k > 0 && k + 1 < n {   continue   }
+            writeln!(f, "{}: ({}, {})&({}, {})", k, cp.re, cp.im, cn.re, cn.im)?;
         }
-        write!(f, "{}", s)
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::read::{read_file, FileFormat};
+
+    /* Returns the coefficients of the drawing of points.txt, its times being
+     * shifted by shift. */
+    fn coeffs_of_points(shift: f64, boundary: &Boundary) -> CoeffsSet {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/points.txt");
+        let set = read_file(path, FileFormat::Text).ok().unwrap();
+        let tt: Vec<f64> = set.tt.iter().map(|t| t + shift).collect();
+        let ss = interpolate_coords(vec![set.xx, set.yy], &tt, &set.mm, boundary).unwrap();
+        compute_fourier_coeffs(&ss[0], &ss[1], 10)
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn coefficients_do_not_depend_on_the_time_origin() {
+        // whole periods, so that the phases stay the same: the periodic path
+        // is closed after 16, the quadratic one lasts 14
+        for (boundary, shift) in [(Boundary::Periodic, 1e6),
+                                  (Boundary::Quadratic, 14.0 * 71429.0)] {
+            let coeffs = coeffs_of_points(0.0, &boundary);
+            let shifted = coeffs_of_points(shift, &boundary);
+            for k in 0..coeffs.ppos.len() {
+                for (c, s) in [(coeffs.ppos[k], shifted.ppos[k]),
+                               (coeffs.nneg[k], shifted.nneg[k])] {
+                    assert!((c - s).modulus() < 1e-6, "{}, k = {}: {} instead of {}",
+                            boundary, k, s, c);
+                }
+            }
+        }
+    }
+}
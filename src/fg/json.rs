@@ -0,0 +1,327 @@
+use std::fmt;
+
+/// A JSON value.
+/// see https://www.json.org
+#[derive(Clone, Debug, PartialEq)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    /// Members, in the order they were written
+    Object(Vec<(String, Json)>),
+}
+
+/// Error found while parsing JSON, at the given line and column (from 1).
+#[derive(Debug)]
+pub struct Error {
+    pub line: usize,
+    pub column: usize,
+    pub message: &'static str,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (line {}, column {})", self.message, self.line, self.column)
+    }
+}
+
+impl Json {
+    /// Returns the member key of an object.
+    pub fn get(&self, key: &str) -> Option<&Json> {
+        match self {
+            Json::Object(members) => members.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self { Json::Number(x) => Some(*x), _ => None }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self { Json::String(s) => Some(s), _ => None }
+    }
+
+    pub fn as_array(&self) -> Option<&[Json]> {
+        match self { Json::Array(a) => Some(a), _ => None }
+    }
+
+    /// Returns the value written with two spaces of indentation per level.
+    /// Arrays and objects only holding numbers, strings and such stay on one
+    /// line, so that lists of points or terms read one per line.
+    pub fn to_pretty_string(&self) -> String {
+        let mut s = String::new();
+        self.write_pretty(&mut s, 0);
+        s.push('\n');
+        s
+    }
+
+    fn write_pretty(&self, s: &mut String, level: usize) {
+        let flat = |v: &Json| !matches!(v, Json::Array(_) | Json::Object(_));
+        let indent = "  ".repeat(level + 1);
+        match self {
+            Json::Array(items) if !items.iter().all(flat) => {
+                s.push_str("[\n");
+                for (i, item) in items.iter().enumerate() {
+                    s.push_str(&indent);
+                    item.write_pretty(s, level + 1);
+                    s.push_str(if i + 1 < items.len() { ",\n" } else { "\n" });
+                }
+                s.push_str(&"  ".repeat(level));
+                s.push(']');
+            }
+            Json::Object(members) if !members.iter().all(|(_, v)| flat(v)) => {
+                s.push_str("{\n");
+                for (i, (key, value)) in members.iter().enumerate() {
+                    s.push_str(&format!("{}{}: ", indent, Json::String(key.clone())));
+                    value.write_pretty(s, level + 1);
+                    s.push_str(if i + 1 < members.len() { ",\n" } else { "\n" });
+                }
+                s.push_str(&"  ".repeat(level));
+                s.push('}');
+            }
+            _ => s.push_str(&self.to_string()),
+        }
+    }
+}
+
+/// Writes the value on a single line. Numbers have as many digits as needed
+/// to be read back exactly, and the ones JSON cannot hold (infinite, NaN) are
+/// written as `null`.
+impl fmt::Display for Json {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Json::Null => write!(f, "null"),
+            Json::Bool(b) => write!(f, "{}", b),
+            Json::Number(x) if x.is_finite() => write!(f, "{}", x),
+            Json::Number(_) => write!(f, "null"),
+            Json::String(s) => {
+                write!(f, "\"")?;
+                for c in s.chars() {
+                    match c {
+                        '"'  => write!(f, "\\\"")?,
+                        '\\' => write!(f, "\\\\")?,
+                        '\n' => write!(f, "\\n")?,
+                        '\r' => write!(f, "\\r")?,
+                        '\t' => write!(f, "\\t")?,
+                        c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+                        c => write!(f, "{}", c)?,
+                    }
+                }
+                write!(f, "\"")
+            }
+            Json::Array(items) => {
+                write!(f, "[")?;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 { write!(f, ", ")? }
+                    write!(f, "{}", item)?;
+                }
+                write!(f, "]")
+            }
+            Json::Object(members) => {
+                write!(f, "{{")?;
+                for (i, (key, value)) in members.iter().enumerate() {
+                    if i > 0 { write!(f, ", ")? }
+                    write!(f, "{}: {}", Json::String(key.clone()), value)?;
+                }
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+/// Parses the JSON text s, which must hold a single value.
+pub fn parse(s: &str) -> Result<Json, Error> {
+    let mut parser = Parser { text: s.as_bytes(), pos: 0 };
+    let value = parser.value()?;
+    parser.skip_whitespace();
+    if parser.pos < parser.text.len() {
+        return Err(parser.error("unexpected characters after the value"))
+    }
+    Ok(value)
+}
+
+/// Recursive descent parser, going through the bytes of the text.
+struct Parser<'a> {
+    text: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn error(&self, message: &'static str) -> Error {
+        let before = &self.text[..self.pos.min(self.text.len())];
+        let line = 1 + before.iter().filter(|&&b| b == b'\n').count();
+        let line_start = before.iter().rposition(|&b| b == b'\n').map_or(0, |i| i + 1);
+        let column = 1 + String::from_utf8_lossy(&before[line_start..]).chars().count();
+        Error { line, column, message }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.text.get(self.pos).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(b' ') | Some(b'\t') | Some(b'\n') | Some(b'\r') = self.peek() {
+            self.pos += 1;
+        }
+    }
+
+    /* Consumes the byte b, that has to come next */
+    fn expect(&mut self, b: u8, message: &'static str) -> Result<(), Error> {
+        if self.peek() != Some(b) {   return Err(self.error(message))   }
+        self.pos += 1;
+        Ok(())
+    }
+
+    fn value(&mut self) -> Result<Json, Error> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some(b'{') => self.object(),
+            Some(b'[') => self.array(),
+            Some(b'"') => Ok(Json::String(self.string()?)),
+            Some(b't') => self.literal("true", Json::Bool(true)),
+            Some(b'f') => self.literal("false", Json::Bool(false)),
+            Some(b'n') => self.literal("null", Json::Null),
+            Some(b'-') | Some(b'0'..=b'9') => self.number(),
+            Some(_) => Err(self.error("unexpected character")),
+            None => Err(self.error("unexpected end of the text")),
+        }
+    }
+
+    fn literal(&mut self, word: &str, value: Json) -> Result<Json, Error> {
+        if self.text[self.pos..].starts_with(word.as_bytes()) {
+            self.pos += word.len();
+            Ok(value)
+        } else {
+            Err(self.error("unknown literal"))
+        }
+    }
+
+    fn number(&mut self) -> Result<Json, Error> {
+        let start = self.pos;
+        while let Some(b'0'..=b'9') | Some(b'-') | Some(b'+') | Some(b'.') | Some(b'e')
+                | Some(b'E') = self.peek() {
+            self.pos += 1;
+        }
+        std::str::from_utf8(&self.text[start..self.pos]).ok()
+            .and_then(|s| s.parse::<f64>().ok())
+            .map(Json::Number)
+            .ok_or_else(|| Parser { text: self.text, pos: start }.error("ill-formed number"))
+    }
+
+    fn string(&mut self) -> Result<String, Error> {
+        self.expect(b'"', "expected a string")?;
+        let mut bytes = Vec::new();
+        loop {
+            match self.peek() {
+                None => return Err(self.error("unterminated string")),
+                Some(b'"') => {
+                    self.pos += 1;
+                    break
+                }
+                Some(b'\\') => {
+                    let escape = self.text.get(self.pos + 1).copied();
+                    self.pos += 2;
+                    let c = match escape {
+                        Some(b'"')  => '"',
+                        Some(b'\\') => '\\',
+                        Some(b'/')  => '/',
+                        Some(b'b')  => '\u{8}',
+                        Some(b'f')  => '\u{c}',
+                        Some(b'n')  => '\n',
+                        Some(b'r')  => '\r',
+                        Some(b't')  => '\t',
+                        Some(b'u')  => self.unicode_escape()?,
+                        _ => return Err(self.error("unknown escape sequence")),
+                    };
+                    let mut buf = [0; 4];
+                    bytes.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+                }
+                Some(b) => {
+                    bytes.push(b);
+                    self.pos += 1;
+                }
+            }
+        }
+        String::from_utf8(bytes).map_err(|_| self.error("invalid UTF-8 in a string"))
+    }
+
+    /* Reads the 4 hexadecimal digits of a \u escape, and the second half of
+     * a surrogate pair if needed */
+    fn unicode_escape(&mut self) -> Result<char, Error> {
+        let first = self.hex4()?;
+        let code = if (0xD800..0xDC00).contains(&first) {
+            if !self.text[self.pos..].starts_with(b"\\u") {
+                return Err(self.error("lone surrogate in a \\u escape"))
+            }
+            self.pos += 2;
+            let second = self.hex4()?;
+            if !(0xDC00..0xE000).contains(&second) {
+                return Err(self.error("lone surrogate in a \\u escape"))
+            }
+            0x10000 + ((first - 0xD800) << 10) + (second - 0xDC00)
+        } else {
+            first
+        };
+        std::char::from_u32(code).ok_or_else(|| self.error("invalid \\u escape"))
+    }
+
+    fn hex4(&mut self) -> Result<u32, Error> {
+        let digits = self.text.get(self.pos..self.pos + 4)
+                              .and_then(|d| std::str::from_utf8(d).ok())
+                              .and_then(|d| u32::from_str_radix(d, 16).ok())
+                              .ok_or_else(|| self.error("invalid \\u escape"))?;
+        self.pos += 4;
+        Ok(digits)
+    }
+
+    fn array(&mut self) -> Result<Json, Error> {
+        self.expect(b'[', "expected an array")?;
+        let mut items = Vec::new();
+        self.skip_whitespace();
+        if self.peek() == Some(b']') {
+            self.pos += 1;
+            return Ok(Json::Array(items))
+        }
+        loop {
+            items.push(self.value()?);
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b',') => self.pos += 1,
+                Some(b']') => {
+                    self.pos += 1;
+                    return Ok(Json::Array(items))
+                }
+                _ => return Err(self.error("expected `,` or `]`")),
+            }
+        }
+    }
+
+    fn object(&mut self) -> Result<Json, Error> {
+        self.expect(b'{', "expected an object")?;
+        let mut members = Vec::new();
+        self.skip_whitespace();
+        if self.peek() == Some(b'}') {
+            self.pos += 1;
+            return Ok(Json::Object(members))
+        }
+        loop {
+            self.skip_whitespace();
+            let key = self.string()?;
+            self.skip_whitespace();
+            self.expect(b':', "expected `:`")?;
+            members.push((key, self.value()?));
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b',') => self.pos += 1,
+                Some(b'}') => {
+                    self.pos += 1;
+                    return Ok(Json::Object(members))
+                }
+                _ => return Err(self.error("expected `,` or `}`")),
+            }
+        }
+    }
+}
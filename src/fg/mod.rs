@@ -1,24 +1,42 @@
+mod color;
 mod complex;
 mod fourier;
 mod fgif;
 mod spline;
 mod read;
+mod png;
+mod sink;
+mod svg;
+mod figure;
+mod pdf;
+mod term;
+mod json;
+mod svgpath;
+mod write;
 
 extern crate clap;
 
+/// Prints an information for the user: on the standard error if the output
+/// is piped to the standard output, so that it does not get mixed with it.
+macro_rules! inform {
+    ($piped:expr, $($arg:tt)*) => {
+        if $piped { eprintln!($($arg)*) } else { println!($($arg)*) }
+    };
+}
+
 use std::fmt;
-use std::num::ParseIntError;
-use clap::{Arg, App};
-use std::f64::consts::PI;
+use clap::{Arg, App, AppSettings, SubCommand};
 
 /// Error type returned by `parse()` function,
 /// represents what's could go wrong.
 /// 
 /// fmt::Display is logically implemented =)
-#[allow(dead_code)]
+#[allow(dead_code, clippy::enum_variant_names)]
 pub enum FgError {
     ReadingError(read::ReadingError),
-    IoError(std::io::Error)
+    IoError(std::io::Error),
+    ArgumentError(String),
+    SplineError(spline::SplineError),
 }
 
 impl fmt::Display for FgError {
@@ -28,6 +46,10 @@ impl fmt::Display for FgError {
                 { write!(f, "Read error: {}", e)           }
             FgError::IoError(e) =>
                 { write!(f, "Error creating the output file: {}", e)}
+            FgError::ArgumentError(e) =>
+                { write!(f, "Invalid argument: {}", e)     }
+            FgError::SplineError(e) =>
+                { write!(f, "Interpolation error: {}", e)  }
         }
     }
 }
@@ -42,6 +64,11 @@ impl std::convert::From<std::io::Error> for FgError {
         FgError::IoError(e)
     }
 }
+impl std::convert::From<spline::SplineError> for FgError {
+    fn from(e: spline::SplineError) -> FgError {
+        FgError::SplineError(e)
+    }
+}
 
 const STD: u8 = 1;
 const COEFFS_ONLY: u8 = 2;
@@ -51,144 +78,728 @@ const DEF_HEIGHT: usize = 200;
 const DEF_WIDTH: usize = 300;
 const DEF_N_STEPS: usize = 200;
 const DEF_N_COEFFS: usize = 5;
+const DEF_FPS: f64 = 25.0;
+const DEF_MARGIN: usize = 10;
+const DEF_STROKE_WIDTH: f64 = 1.0;
+/// Number of shades of each colour used for anti-aliasing the formats that
+/// have a palette.
+const AA_LEVELS: usize = 8;
 
 /// Parses arguments provided to the program, and process to execution
 #[allow(dead_code)]
 pub fn parse() -> Result<(), FgError> {
-    
     let matches = app_args();
+    match matches.subcommand() {
+        ("compute", Some(m)) => compute(m),
+        ("render",  Some(m)) => render(m),
+        ("inspect", Some(m)) => inspect(m),
+        ("convert", Some(m)) => convert(m),
+        // a subcommand is required
+        _ => unreachable!(),
+    }
+}
 
-    let fc = get_color(& matches, "fcolor", "0x000000");
-    let bc = get_color(& matches, "bcolor", "0xFFFFFF");
+/// Computes the Fourier coefficients of the points of the input, and writes
+/// them in a file that `render` can read back.
+fn compute(matches: & clap::ArgMatches) -> Result<(), FgError> {
+    let input = matches.value_of("input").unwrap();
+    let output = matches.value_of("output").unwrap_or("coeffs.txt");
+    let n_coeffs = get_value(matches, "n_coeffs", DEF_N_COEFFS)? + 1;
+
+    let (_, sx, sy) = read_drawing(matches, input, file_format(matches, "input_format",
+        input))?;
+    let coeffs = fourier::compute_fourier_coeffs(& sx, & sy, n_coeffs);
+    let header = read::CoeffsHeader {
+        period: Some(sx.end() - sx.start()),
+        origin: Some(sx.start()),
+        terms: None,
+        source: Some(String::from(input)),
+    };
+    write::write_fourier_coeffs(output, &coeffs, &header, file_format(matches, "format",
+        output))?;
+    println!("Wrote {} coeffs in {}", n_coeffs, output);
+    Ok(())
+}
+
+/// Renders the input, following the many options of the `render` subcommand.
+fn render(matches: & clap::ArgMatches) -> Result<(), FgError> {
+    let fc = get_color(matches, "fcolor", (0, 0, 0))?;
+    let bc = get_color(matches, "bcolor", (255, 255, 255))?;
+    // each element defaults to the foreground, circles being more discreet
+    let inks = vec![get_color(matches, "arms_color",    fc)?,
+                    get_color(matches, "circles_color", color::mix(bc, fc, 0.5))?,
+                    get_color(matches, "trace_color",   fc)?,
+                    get_color(matches, "tip_color",     fc)?,
+                    get_color(matches, "points_color",  fc)?];
 
     let input = matches.value_of("input").unwrap();
     let output = matches.value_of("output").unwrap_or("output.gif");
+    let piped = output == sink::STDOUT;
 
-    let gw = get_value(& matches, "width", DEF_WIDTH);
-    let gh = get_value(& matches, "width", DEF_HEIGHT);
-    let n_steps = get_value(& matches, "width", DEF_N_STEPS);
-    let n_coeffs = get_value(& matches, "n_coeffs", DEF_N_COEFFS) + 1;
+    let gw = get_value(matches, "gifwidth", DEF_WIDTH)?;
+    let gh = get_value(matches, "gifheight", DEF_HEIGHT)?;
+    let fps = get_float(matches, "fps", DEF_FPS)?;
+    if fps.is_nan() || fps <= 0.0 {
+        return Err(FgError::ArgumentError(String::from("--fps has to be positive")))
+    }
+    let n_steps = match parse_arg::<f64>(matches, "duration", "a number")? {
+        Some(d) if d.is_nan() || d <= 0.0 =>
+            return Err(FgError::ArgumentError(String::from("--duration has to be positive"))),
+        Some(d) => ((d * fps).round() as usize).max(1),
+        None    => get_value(matches, "n_steps", DEF_N_STEPS)?,
+    };
+    let n_coeffs = get_value(matches, "n_coeffs", DEF_N_COEFFS)? + 1;
+    let scale = parse_arg::<f64>(matches, "scale", "a number")?;
+    if scale.is_some_and(|s| s.is_nan() || s <= 0.0) {
+        return Err(FgError::ArgumentError(String::from("--scale has to be positive")))
+    }
+    let offset = match matches.value_of("offset") {
+        None    => None,
+        Some(s) => Some(parse_pair(s).ok_or_else(|| FgError::ArgumentError(
+            format!("`{}` is not a pair `x,y` (--offset)", s)))?),
+    };
+
+    let mut opts = fgif::DrawOptions {
+        width: gw,
+        height: gh,
+        n_steps,
+        circles: !matches.is_present("no_circles"),
+        order: match matches.value_of("order").unwrap_or("index") {
+            "magnitude" => fgif::ArmOrder::Magnitude,
+            "frequency" => fgif::ArmOrder::Frequency,
+            _ => fgif::ArmOrder::Index,
+        },
+        anchor: if matches.is_present("recenter") { fgif::Anchor::Center }
+                else                              { fgif::Anchor::Dc     },
+        margin: get_value(matches, "margin", DEF_MARGIN)?,
+        scale,
+        offset,
+        antialias: matches.is_present("antialias"),
+        arm_width: get_width(matches, "arm_width")?,
+        trace_width: get_width(matches, "trace_width")?,
+        trail: parse_arg::<usize>(matches, "trail", "a whole number")?.filter(|&n| n > 0),
+        scheme: match matches.value_of("scheme").unwrap_or("mono") {
+            "frequency" => fgif::ColorScheme::Frequency,
+            _ => fgif::ColorScheme::Mono,
+        },
+        show_points: matches.is_present("show_points"),
+        fps,
+        loops: get_value(matches, "loops", 0)?,
+        pause: get_value(matches, "pause", 0)?,
+        delta: !matches.is_present("no_delta"),
+        compare: matches.is_present("compare_size"),
+    };
+    let preview = matches.is_present("preview");
+    if preview {
+        // one pixel per braille dot, filling the terminal but its last line,
+        // where the prompt comes back
+        let (columns, lines) = term::size();
+        opts.width = columns * term::CELL.0;
+        opts.height = lines.saturating_sub(1).max(1) * term::CELL.1;
+        opts.margin = term::CELL.0;
+        opts.antialias = false;
+        opts.arm_width = 1.0;
+        opts.trace_width = 1.0;
+    }
+    let format = match matches.value_of("format") {
+        _ if preview => sink::Format::Terminal,
+        Some("gif")  => sink::Format::Gif,
+        Some("png")  => sink::Format::PngSequence,
+        Some("apng") => sink::Format::Apng,
+        Some("svg")  => sink::Format::Svg,
+        Some("pdf")  => sink::Format::Pdf,
+        Some("y4m")  => sink::Format::Y4m,
+        _            => sink::Format::from_path(output),
+    };
+    if format == sink::Format::Y4m && sink::Y4m::rate(opts.fps).0 == 0 {
+        return Err(FgError::ArgumentError(String::from("--fps is too low for a Y4M video, \
+            whose frame rate is written to the thousandth")))
+    }
+    let levels = if opts.antialias && !format.true_color() { AA_LEVELS } else { 1 };
+    let palette = fgif::Palette::new(bc, inks, levels,
+                                     format == sink::Format::Gif && opts.delta);
 
-    let ctype = match matches.value_of("test").unwrap_or("std") {
-        "std" => STD,
-        "coeffs" => COEFFS_ONLY,
-        "spline" => SPLINE,
-        _ => STD,
+    let in_format = file_format(matches, "input_format", input);
+    let ctype = match matches.value_of("type") {
+        Some("coeffs") => COEFFS_ONLY,
+        Some("spline") => SPLINE,
+        Some(_)        => STD,
+        None => match read::guess_content(input, in_format)? {
+            read::Content::Coeffs => COEFFS_ONLY,
+            read::Content::Points => STD,
+        },
     };
 
+    if piped && format != sink::Format::Y4m {
+        return Err(FgError::ArgumentError(String::from(
+            "only Y4M video can be written to the standard output")))
+    }
+    // PDF documents can only hold still drawings
+    let still = matches.is_present("still") || format == sink::Format::Pdf;
+    if still && format != sink::Format::Svg && format != sink::Format::Pdf {
+        return Err(FgError::ArgumentError(String::from(
+            "still drawings are only available as SVG or PDF")))
+    }
+
     if ctype == COEFFS_ONLY {
-        let coeffs = read::read_fourier_coeffs(input)?;
-        println!("coeffs: \n{}", coeffs);
+        let (coeffs, header) = read::read_fourier_coeffs(input, in_format)?;
+        inform!(piped, "coeffs: \n{}", coeffs);
 
-        fgif::draw_fourier_coeff(coeffs, output, gw, gh, (0.0, 2.0*PI), n_steps,
-            &[bc.0, bc.1, bc.2, fc.0, fc.1, fc.2])?;
+        let t_span = header.t_span();
+        if still {
+            let fig = figure::still(None, Some((&coeffs, t_span)), &[], &opts, &palette);
+            write_still(&fig, output, format)?;
+        } else {
+            let report = draw_coeffs(coeffs, &[], t_span, output, format, &opts, &palette)?;
+            print_report(&report, piped);
+        }
     }
     else if ctype == SPLINE {
-        let set = read::read_file(input)?;
-        let ss = spline::interpolate_coords(vec![set.xx, set.yy], &set.tt, &set.mm);
-        let sx = ss[0].clone();
-        let sy = ss[1].clone();
+        let (set, sx, sy) = read_drawing(matches, input, in_format)?;
+        let marks = set.points();
 
-        fgif::draw_spline(sx, sy, output, gw, gh, n_steps, 
-            &[bc.0, bc.1, bc.2, fc.0, fc.1, fc.2])?;
+        if still || format == sink::Format::Svg {
+            let fig = figure::still(Some((&sx, &sy)), None, &marks, &opts, &palette);
+            write_still(&fig, output, format)?;
+        } else {
+            let mut sink = sink::create(output, format, &opts)?;
+            let report = fgif::draw_spline(sx, sy, &marks, &mut *sink, &opts, &palette)?;
+            print_report(&report, piped);
+        }
     }
     else {
-        let set = read::read_file(input)?;
-        let ss = spline::interpolate_coords(vec![set.xx, set.yy], &set.tt, &set.mm);
-        let sx = ss[0].clone();
-        let sy = ss[1].clone();
+        let (set, sx, sy) = read_drawing(matches, input, in_format)?;
+        let marks = set.points();
         
         let coeffs = fourier::compute_fourier_coeffs(& sx, & sy, n_coeffs);
 
-        println!("{}", coeffs);
-        fgif::draw_fourier_coeff(coeffs, output, gw, gh, (sx.start(), sx.end()),
-            n_steps, &[bc.0, bc.1, bc.2, fc.0, fc.1, fc.2])?;  
+        inform!(piped, "{}", coeffs);
+        let t_span = (sx.start(), sx.end());
+        if still {
+            let fig = figure::still(Some((&sx, &sy)), Some((&coeffs, t_span)), &marks,
+                &opts, &palette);
+            write_still(&fig, output, format)?;
+        } else {
+            let report = draw_coeffs(coeffs, &marks, t_span, output, format, &opts,
+                &palette)?;
+            print_report(&report, piped);
+        }
+    }
+    if !still && !preview {
+        inform!(piped, "Wrote {} frames in {} ({}, {}), with {} coeffs", n_steps, output,
+            gw, gh, n_coeffs);
     }
-    println!("Wrote {} frames in {} ({}, {}), with {} coeffs", n_steps, output,
-        gw, gh, n_coeffs);
     Ok(())
 }
 
+/// Prints statistics on the spline of the input, if it holds points, and on
+/// its spectrum: the magnitude of each harmonic, and the share of the energy
+/// of the drawing held by the harmonics up to it.
+fn inspect(matches: & clap::ArgMatches) -> Result<(), FgError> {
+    let input = matches.value_of("input").unwrap();
+    let n_coeffs = get_value(matches, "n_coeffs", DEF_N_COEFFS)? + 1;
+
+    let in_format = file_format(matches, "input_format", input);
+    let (coeffs, power) = match input_content(matches, input, in_format)? {
+        read::Content::Points => {
+            let (set, sx, sy) = read_drawing(matches, input, in_format)?;
+            let n_points = set.tt.len();
+            // a path that does not end where it starts is closed by one more part
+            let n_linear = set.mm[..sx.num_parts()].iter().filter(|&&m| m).count();
+            let coeffs = fourier::compute_fourier_coeffs(& sx, & sy, n_coeffs);
+
+            // sampled, finely enough for statistics
+            let n = 64 * sx.num_parts();
+            let period = sx.end() - sx.start();
+            let samples: Vec<complex::Complex> = (0..=n)
+                .map(|i| (sx.start() + period * i as f64 / n as f64).min(sx.end()))
+                .map(|t| complex::Complex { re: sx.eval(t), im: sy.eval(t) })
+                .collect();
+            let length: f64 = samples.windows(2).map(|w| (w[1] - w[0]).modulus()).sum();
+            let (mut min, mut max) = (samples[0], samples[0]);
+            for p in samples.iter() {
+                min.re = min.re.min(p.re);  max.re = max.re.max(p.re);
+                min.im = min.im.min(p.im);  max.im = max.im.max(p.im);
+            }
+            // mean of |f - c_0|², that the harmonics share (Parseval)
+            let power = samples[..n].iter().map(|&p| (p - coeffs.dc()).modulus().powi(2))
+                                    .sum::<f64>() / n as f64;
+
+            println!("Spline: {} points, {} parts ({} linear), t from {} to {}", n_points,
+                sx.num_parts(), n_linear, sx.start(), sx.end());
+            println!("Length: {:.4}, bounding box: {} to {}", length, min, max);
+            (coeffs, Some(power))
+        }
+        read::Content::Coeffs => (read::read_fourier_coeffs(input, in_format)?.0, None),
+    };
+
+    let magnitudes: Vec<(f64, f64)> = coeffs.ppos.iter().zip(coeffs.nneg.iter())
+        .skip(1)
+        .map(|(cp, cn)| (cp.modulus(), cn.modulus()))
+        .collect();
+    // without the spline, the harmonics given are all there is
+    let total = power.unwrap_or_else(||
+        magnitudes.iter().map(|(p, n)| p * p + n * n).sum());
+
+    println!("Spectrum: c_0 = {}", coeffs.dc());
+    println!("{:>4}  {:>12}  {:>12}  {:>8}", "k", "|c_k|", "|c_-k|", "energy");
+    let mut cumulated = 0.0;
+    for (k, (p, n)) in magnitudes.iter().enumerate() {
+        cumulated += p * p + n * n;
+        let share = if total > 0.0 { 100.0 * cumulated / total } else { 100.0 };
+        println!("{:>4}  {:>12.6}  {:>12.6}  {:>7.3}%", k + 1, p, n, share);
+    }
+    Ok(())
+}
+
+/// Reads the input, and writes it in the output again, in the format given by
+/// `--format` or by its extension.
+fn convert(matches: & clap::ArgMatches) -> Result<(), FgError> {
+    let input = matches.value_of("input").unwrap();
+    let output = matches.value_of("output").unwrap();
+    let in_format = file_format(matches, "input_format", input);
+    let out_format = file_format(matches, "format", output);
+
+    match input_content(matches, input, in_format)? {
+        read::Content::Points =>
+            write::write_file(output, &read_points(matches, input, in_format)?, out_format)?,
+        read::Content::Coeffs => {
+            let (coeffs, header) = read::read_fourier_coeffs(input, in_format)?;
+            write::write_fourier_coeffs(output, &coeffs, &header, out_format)?
+        }
+    }
+    println!("Converted {} into {}", input, output);
+    Ok(())
+}
+
+/// Returns what the input holds, as given by `--type` or guessed.
+fn input_content(matches: & clap::ArgMatches, input: &str, format: read::FileFormat)
+    -> Result<read::Content, FgError> {
+    Ok(match matches.value_of("type") {
+        Some("coeffs") => read::Content::Coeffs,
+        Some(_)        => read::Content::Points,
+        None           => read::guess_content(input, format)?,
+    })
+}
+
+/// Returns the format of the file at path, as given by the argument name or
+/// by its extension.
+fn file_format(matches: & clap::ArgMatches, name: &str, path: &str) -> read::FileFormat {
+    match matches.value_of(name) {
+        Some("json") => read::FileFormat::Json,
+        Some("svg")  => read::FileFormat::Svg,
+        Some("columns") => read::FileFormat::Columns,
+        Some(_)      => read::FileFormat::Text,
+        None         => read::FileFormat::from_path(path),
+    }
+}
+
+/// Reads the points of the input, in the columns given by `--columns` if it
+/// holds columns. `--boundary` overrides the boundary they are given with.
+fn read_points(matches: & clap::ArgMatches, input: &str, format: read::FileFormat)
+    -> Result<read::PointsSet, FgError> {
+    let mut set = match (format, matches.value_of("columns")) {
+        (read::FileFormat::Columns, Some(c)) =>
+            read::read_columns(input, &c.parse::<read::ColumnMap>()?)?,
+        _ => read::read_file(input, format)?,
+    };
+    if let Some(b) = matches.value_of("boundary") {
+        set.boundary = Some(b.parse::<spline::Boundary>()?);
+    }
+    Ok(set)
+}
+
+/// Reads the points of the input, and returns them with the splines of their
+/// x and y coordinates. The splines of SVG paths follow their curves exactly,
+/// instead of interpolating the ends of their segments, so that they take no
+/// `--boundary`, and an open path is closed by a straight line. The others have the boundary they are given with, or are
+/// periodic, as Fourier series are, closing the path if needed.
+fn read_drawing(matches: & clap::ArgMatches, input: &str, format: read::FileFormat)
+    -> Result<(read::PointsSet, spline::Spline, spline::Spline), FgError> {
+    if format == read::FileFormat::Svg {
+        if matches.is_present("boundary") {
+            return Err(FgError::ArgumentError(String::from("--boundary does not apply to \
+                SVG paths, whose curves are followed exactly")))
+        }
+        let segments = svgpath::read_svg(input)?;
+        let (sx, sy) = svgpath::splines(&segments);
+        return Ok((svgpath::points(&segments), sx, sy))
+    }
+    let set = read_points(matches, input, format)?;
+    let ss = spline::interpolate_coords(vec![set.xx.clone(), set.yy.clone()], &set.tt,
+        &set.mm, set.boundary.as_ref().unwrap_or(&spline::Boundary::Periodic))?;
+    Ok((set, ss[0].clone(), ss[1].clone()))
+}
+
 fn app_args() -> clap::ArgMatches<'static> {
     App::new("fg")
         .version("0.2.0")
         .author("François Straet")
         .about("Drawings with Fourier series")
-        .arg(Arg::with_name("input")
-            .help("Sets the input file, containing the points of the drawing formatted as: `t: (x, y)`")
-            .required(true)
-            .index(1))
-        .arg(Arg::with_name("output")
-            .short("o")
-            .long("output")
-            .takes_value(true)
-            .help("Sets the name of output file, `output.gif` if not provided"))
-        .arg(Arg::with_name("fcolor")
-            .short("f")
-            .long("fcolor")
-            .takes_value(true)
-            .help("Sets the foreground color used in the output (hexcode)"))
-        .arg(Arg::with_name("bcolor")
-            .short("b")
-            .long("bcolor")
-            .takes_value(true)
-            .help("Sets the background color used in the output (hexcode)"))
-        .arg(Arg::with_name("width")
-            .short("W")
-            .long("gifwidth")
-            .takes_value(true)
-            .help("Sets the output's width"))
-        .arg(Arg::with_name("height")
-            .short("H")
-            .long("gifheight")
-            .takes_value(true)
-            .help("Sets the output's height"))
-        .arg(Arg::with_name("n_steps")
-                .short("n")
-                .long("n-steps")
-                .takes_value(true)
-                .help("Sets the numbers of frames of the output."))
-        .arg(Arg::with_name("n_coeffs")
-            .short("c")
-            .long("n-coeffs")
-            .takes_value(true)
-            .help("Sets Fourier coefficients computed and used."))
-        // .arg(Arg::with_name("coeffs")
-        //     .help("Ouputs drawing of custom Fourier coefficients in the input, which has to be formatted as\n \
-        //             `(Re(c_k),Im(c_k))&(Re(c_-k) , Im(c_-k))`")
-        //     .long("coeffs")
-        //     .short("c"))
-        .arg(Arg::with_name("test")
-            .takes_value(true)
-            .possible_values(&["coeffs", "spline"])
-            .help("Coeffs: uputs drawing of custom Fourier coefficients in the input, which has to be formatted as\n \
-                    `(Re(c_k),Im(c_k))&(Re(c_-k) , Im(c_-k))` \n
-                    Spline: only draws the spline.")
-            .long("type")
-            .short("t"))
+        .setting(AppSettings::SubcommandRequiredElseHelp)
+        .subcommand(SubCommand::with_name("compute")
+            .about("Computes the Fourier coefficients of a drawing, and writes them in a \
+                    file that `render` reads")
+            .arg(input_arg())
+            .arg(Arg::with_name("output")
+                .short("o")
+                .long("output")
+                .takes_value(true)
+                .help("Sets the name of the output file, `coeffs.txt` if not provided"))
+            .arg(file_format_arg("format", &["text", "json"])
+                .help("Sets the format of the output file, given by its extension if not \
+                       provided: JSON for `.json` files, text otherwise"))
+            .arg(input_format_arg())
+            .arg(columns_arg())
+            .arg(boundary_arg())
+            .arg(n_coeffs_arg()))
+        .subcommand(SubCommand::with_name("render")
+            .about("Draws the epicycles of a drawing, or of Fourier coefficients")
+            .arg(input_arg())
+            .arg(Arg::with_name("output")
+                .short("o")
+                .long("output")
+                .takes_value(true)
+                .help("Sets the name of output file, `output.gif` if not provided. Its extension \
+                       gives the format if `--format` is not provided: `.png` for a sequence \
+                       of numbered PNG files, `.apng` for an animated PNG, `.svg` for an animated \
+                       SVG, `.pdf` for a still PDF drawing, `.y4m` for a Y4M video, GIF \
+                       otherwise. `-` writes a Y4M video to the standard output"))
+            .arg(Arg::with_name("format")
+                .long("format")
+                .takes_value(true)
+                .possible_values(&["gif", "png", "apng", "svg", "pdf", "y4m"])
+                .help("Sets the format of the output: GIF, sequence of PNG files, animated PNG, \
+                       animated SVG, still PDF drawing or uncompressed Y4M video"))
+            .arg(Arg::with_name("preview")
+                .long("preview")
+                .conflicts_with_all(&["output", "format", "still"])
+                .help("Plays the animation, or shows the spline, in the terminal with braille \
+                       characters instead of writing an output. It fills the terminal, whose \
+                       size is given by the COLUMNS and LINES environment variables if set, \
+                       by `stty size` otherwise"))
+            .arg(Arg::with_name("still")
+                .long("still")
+                .help("Writes a still drawing of the spline, as exact curves, and of its Fourier \
+                       reconstruction, instead of an animation. The output has to be SVG or PDF"))
+            .arg(Arg::with_name("fcolor")
+                .short("f")
+                .long("fcolor")
+                .takes_value(true)
+                .help("Sets the foreground color used in the output, as `0xrrggbb`, `#rrggbb`, \
+                       `rgb(r, g, b)` or a CSS color name"))
+            .arg(Arg::with_name("bcolor")
+                .short("b")
+                .long("bcolor")
+                .takes_value(true)
+                .help("Sets the background color used in the output"))
+            .arg(Arg::with_name("arms_color")
+                .long("arms-color")
+                .takes_value(true)
+                .help("Sets the color of the arms, the foreground color if not provided"))
+            .arg(Arg::with_name("circles_color")
+                .long("circles-color")
+                .takes_value(true)
+                .help("Sets the color of the circles, between the foreground and background \
+                       colors if not provided"))
+            .arg(Arg::with_name("trace_color")
+                .long("trace-color")
+                .takes_value(true)
+                .help("Sets the color of the traced drawing, the foreground color if not provided"))
+            .arg(Arg::with_name("tip_color")
+                .long("tip-color")
+                .takes_value(true)
+                .help("Sets the color of the tip of the last arm, the foreground color if not provided"))
+            .arg(Arg::with_name("points_color")
+                .long("points-color")
+                .takes_value(true)
+                .help("Sets the color of the control points, the foreground color if not provided"))
+            .arg(Arg::with_name("scheme")
+                .long("scheme")
+                .takes_value(true)
+                .possible_values(&["mono", "frequency"])
+                .help("Sets how the epicycles are colored: all with the arms and circles colors, \
+                       or each with a hue given by its frequency"))
+            .arg(Arg::with_name("show_points")
+                .long("show-points")
+                .help("Marks the control points of the input on the drawing"))
+            .arg(Arg::with_name("gifwidth")
+                .short("W")
+                .long("gifwidth")
+                .takes_value(true)
+                .help("Sets the output's width"))
+            .arg(Arg::with_name("gifheight")
+                .short("H")
+                .long("gifheight")
+                .takes_value(true)
+                .help("Sets the output's height"))
+            .arg(Arg::with_name("n_steps")
+                    .short("n")
+                    .long("n-steps")
+                    .takes_value(true)
+                    .help("Sets the numbers of frames of the output."))
+            .arg(Arg::with_name("fps")
+                .long("fps")
+                .takes_value(true)
+                .help("Sets the number of frames per second of the output, 25 if not provided. \
+                       Most viewers do not go beyond 50."))
+            .arg(Arg::with_name("duration")
+                .short("d")
+                .long("duration")
+                .takes_value(true)
+                .conflicts_with("n_steps")
+                .help("Sets the duration of one cycle in seconds, the number of frames being \
+                       deduced from the frame rate"))
+            .arg(Arg::with_name("loops")
+                .long("loops")
+                .takes_value(true)
+                .help("Sets the number of times the output is played, forever if 0 or not provided"))
+            .arg(Arg::with_name("pause")
+                .long("pause")
+                .takes_value(true)
+                .help("Holds the last frame of each cycle for the given number of extra frames"))
+            .arg(Arg::with_name("no_delta")
+                .long("no-delta")
+                .help("Writes every frame whole, instead of only the part that changed"))
+            .arg(Arg::with_name("compare_size")
+                .long("compare-size")
+                .conflicts_with("no_delta")
+                .help("Also encodes every GIF or APNG frame whole, to tell how much smaller \
+                       writing only the part that changed made the output. Doubles the \
+                       encoding time"))
+            .arg(n_coeffs_arg())
+            .arg(Arg::with_name("no_circles")
+                .long("no-circles")
+                .help("Do not draw the circles of the epicycles, only their arms"))
+            .arg(Arg::with_name("recenter")
+                .long("recenter")
+                .help("Ignores the DC coefficient c_0 and centers the drawing, instead of \
+                       starting the epicycles at c_0"))
+            .arg(Arg::with_name("margin")
+                .long("margin")
+                .takes_value(true)
+                .help("Sets the space left around the drawing when it is fitted to the output, in pixels"))
+            .arg(Arg::with_name("scale")
+                .long("scale")
+                .takes_value(true)
+                .help("Sets the number of pixels per unit, instead of fitting the drawing to the output"))
+            .arg(Arg::with_name("offset")
+                .long("offset")
+                .takes_value(true)
+                .allow_hyphen_values(true)
+                .help("Sets the translation `x,y` applied to the drawing before scaling, \
+                       instead of centering it"))
+            .arg(Arg::with_name("antialias")
+                .short("a")
+                .long("antialias")
+                .help("Anti-aliases the lines of the output: GIF frames use 8 shades of each \
+                       colour going to the background, PNG, APNG and Y4M frames blend colours"))
+            .arg(Arg::with_name("arm_width")
+                .long("arm-width")
+                .takes_value(true)
+                .help("Sets the stroke width of the arms, in pixels"))
+            .arg(Arg::with_name("trace_width")
+                .long("trace-width")
+                .takes_value(true)
+                .help("Sets the stroke width of the traced drawing, in pixels"))
+            .arg(Arg::with_name("trail")
+                .long("trail")
+                .takes_value(true)
+                .help("Makes the traced drawing fade out over the given number of frames, \
+                       instead of keeping it forever"))
+            .arg(Arg::with_name("order")
+                .long("order")
+                .takes_value(true)
+                .possible_values(&["index", "magnitude", "frequency"])
+                .help("Sets the order in which the epicycles are chained: by harmonic index \
+                       (+1, -1, +2, ...), by decreasing magnitude or by increasing frequency"))
+            .arg(type_arg(&["points", "coeffs", "spline"])
+                .help("Sets what the input holds: points, whose interpolation is drawn with \
+                       epicycles, Fourier coefficients, or points whose spline only is \
+                       drawn. Guessed from the input if not provided"))
+            .arg(input_format_arg())
+            .arg(columns_arg())
+            .arg(boundary_arg()))
+        .subcommand(SubCommand::with_name("inspect")
+            .about("Prints statistics on the spline of a drawing and on its spectrum")
+            .arg(input_arg())
+            .arg(type_arg(&["points", "coeffs"])
+                .help("Sets what the input holds, guessed from the input if not provided"))
+            .arg(input_format_arg())
+            .arg(columns_arg())
+            .arg(boundary_arg())
+            .arg(n_coeffs_arg()))
+        .subcommand(SubCommand::with_name("convert")
+            .about("Translates an input file into another input format")
+            .arg(input_arg())
+            .arg(Arg::with_name("output")
+                .short("o")
+                .long("output")
+                .takes_value(true)
+                .required(true)
+                .help("Sets the name of the output file"))
+            .arg(file_format_arg("format", &["text", "json", "columns"])
+                .help("Sets the format of the output file, given by its extension if not \
+                       provided: JSON for `.json` files, columns for `.mat`, `.csv`, `.tsv` \
+                       and `.dat` files, text otherwise. Columns only hold points"))
+            .arg(input_format_arg())
+            .arg(columns_arg())
+            .arg(boundary_arg())
+            .arg(type_arg(&["points", "coeffs"])
+                .help("Sets what the input holds, guessed from the input if not provided")))
         .get_matches()
 }
 
+fn input_arg() -> Arg<'static, 'static> {
+    Arg::with_name("input")
+        .help("Sets the input file, containing the points of the drawing formatted as \
+               `t: (x, y)`, or Fourier coefficients formatted as \
+               `(Re(c_k), Im(c_k))&(Re(c_-k), Im(c_-k))`, or the same as JSON, or an SVG \
+               drawing whose paths are drawn")
+        .required(true)
+        .index(1)
+}
+
+fn n_coeffs_arg() -> Arg<'static, 'static> {
+    Arg::with_name("n_coeffs")
+        .short("c")
+        .long("n-coeffs")
+        .takes_value(true)
+        .help("Sets Fourier coefficients computed and used.")
+}
+
+fn input_format_arg() -> Arg<'static, 'static> {
+    file_format_arg("input_format", &["text", "json", "svg", "columns"])
+        .long("input-format")
+        .help("Sets the format of the input file, given by its extension if not provided: \
+               JSON for `.json` files, SVG for `.svg` files, whose paths are drawn, \
+               delimited columns for `.mat`, `.csv`, `.tsv` and `.dat` files, text \
+               otherwise")
+}
+
+fn columns_arg() -> Arg<'static, 'static> {
+    Arg::with_name("columns")
+        .long("columns")
+        .takes_value(true)
+        .help("Sets the columns of the times, the coordinates and optionally the \
+               interpolation method of the points, when the input holds columns, as \
+               `t,x,y[,mode]`. Each is a position from 1 or a name of the header row. \
+               `1,2,3` if not provided")
+}
+
+fn boundary_arg() -> Arg<'static, 'static> {
+    Arg::with_name("boundary")
+        .long("boundary")
+        .takes_value(true)
+        .help("Sets the conditions at the ends of the spline through the points: \
+               `periodic`, `natural`, `not-a-knot` (as Octave's `spline`), `quadratic`, or \
+               `clamped (x, y) (x, y)` with the slopes at the start and at the end. Overrides \
+               a `# boundary:` line of the points, `periodic` if neither is provided")
+}
+
+fn file_format_arg(name: &'static str, values: &'static [&'static str])
+    -> Arg<'static, 'static> {
+    Arg::with_name(name)
+        .long("format")
+        .takes_value(true)
+        .possible_values(values)
+}
+
+fn type_arg(values: &'static [&'static str]) -> Arg<'static, 'static> {
+    Arg::with_name("type")
+        .short("t")
+        .long("type")
+        .takes_value(true)
+        .possible_values(values)
+}
+
+/// Draws the animation of coeffs in output, in the given format.
+fn draw_coeffs(coeffs: fourier::CoeffsSet, marks: &[complex::Complex], t_span: (f64, f64),
+    output: &str, format: sink::Format, opts: &fgif::DrawOptions, palette: &fgif::Palette)
+    -> Result<fgif::DrawReport, FgError> {
+    if format == sink::Format::Svg {
+        let file = std::fs::File::create(output)?;
+        Ok(svg::draw_fourier_coeff(coeffs, marks, file, t_span, opts, palette)?)
+    } else {
+        let mut sink = sink::create(output, format, opts)?;
+        Ok(fgif::draw_fourier_coeff(coeffs, marks, &mut *sink, t_span, opts, palette)?)
+    }
+}
+
+/// Writes the still drawing fig in output, in the given vector format.
+fn write_still(fig: &figure::Figure, output: &str, format: sink::Format)
+    -> Result<(), FgError> {
+    let file = std::fs::File::create(output)?;
+    let bytes = match format {
+        sink::Format::Pdf => pdf::write_figure(fig, file)?,
+        _                 => svg::write_figure(fig, file)?,
+    };
+    println!("Wrote a still drawing in {} ({} bytes)", output, bytes);
+    Ok(())
+}
+
+/// Tells the user by how much the drawing has been shifted, if it was, and
+/// what delta frames saved.
+fn print_report(report: &fgif::DrawReport, piped: bool) {
+    let offset = report.offset;
+    if offset.re != 0.0 || offset.im != 0.0 {
+        inform!(piped, "Drawing recentered, shifted by {}", offset);
+    }
+    if let Some(full) = report.full_bytes {
+        let saved = 100.0 * (1.0 - report.bytes as f64 / full as f64);
+        inform!(piped, "Delta frames: {} bytes instead of {} ({:.1}% saved)", report.bytes,
+            full, saved);
+    }
+}
+
 /// Get a color desribed in argument, default value if not present.
-fn get_color(matches: & clap::ArgMatches, name: & str, def: & str) 
-    -> (u8, u8, u8) {
-    let s = matches.value_of(name).unwrap_or(def);
-    color_from_hex(s).unwrap()
+fn get_color(matches: & clap::ArgMatches, name: & str, def: color::Rgb)
+    -> Result<color::Rgb, FgError> {
+    match matches.value_of(name) {
+        None => Ok(def),
+        Some(s) => color::parse_color(s).ok_or_else(||
+            FgError::ArgumentError(format!("`{}` is not a color ({})", s, flag(name)))),
+    }
 }
 
-/* Parses a color written in hexcode (0xrrggbb) to a tuple (r, g, b) */
-fn color_from_hex(s: &str) -> Result <(u8, u8, u8), ParseIntError> {
-    let without_prefix = s.trim_start_matches("0x");
-    // println!("{}, then {}", s, without_prefix);
-    let r = u8::from_str_radix(&without_prefix[0..2], 16)?;
-    let g = u8::from_str_radix(&without_prefix[2..4], 16)?;
-    let b = u8::from_str_radix(&without_prefix[4..6], 16)?;
+/// Returns the long flag of the argument name, all of them being their name
+/// with dashes.
+fn flag(name: &str) -> String {
+    format!("--{}", name.replace('_', "-"))
+}
 
-    Ok((r, g, b))
+/// Parses a pair of numbers written as `x,y`.
+fn parse_pair(s: &str) -> Option<(f64, f64)> {
+    let parts: Vec<&str> = s.split(',').collect();
+    if parts.len() != 2 {   return None   }
+    let x = parts[0].trim().parse::<f64>().ok()?;
+    let y = parts[1].trim().parse::<f64>().ok()?;
+    Some((x, y))
+}
+
+/// Parses the value of the argument name if present, failing with its flag
+/// and the value if it is not `what` T is.
+fn parse_arg<T: std::str::FromStr>(matches: & clap::ArgMatches, name: & str, what: & str)
+    -> Result<Option<T>, FgError> {
+    matches.value_of(name).map(|s| s.parse::<T>().map_err(|_| FgError::ArgumentError(
+        format!("`{}` is not {} ({})", s, what, flag(name))))).transpose()
+}
+
+fn get_float(matches: & clap::ArgMatches, name: & str, def: f64) -> Result<f64, FgError> {
+    Ok(parse_arg(matches, name, "a number")?.unwrap_or(def))
+}
+
+/// Returns the stroke width given by the argument name, which has to be a
+/// positive number of pixels.
+fn get_width(matches: & clap::ArgMatches, name: & str) -> Result<f64, FgError> {
+    let width = get_float(matches, name, DEF_STROKE_WIDTH)?;
+    if !width.is_finite() || width <= 0.0 {
+        return Err(FgError::ArgumentError(format!("{} has to be positive", flag(name))))
+    }
+    Ok(width)
 }
 
-fn get_value(matches: & clap::ArgMatches, name: & str, def: usize) -> usize {
-    let def_str = def.to_string();
-    let s = matches.value_of(name).unwrap_or(& def_str);
-    s.parse::<usize>().unwrap_or(def)
+fn get_value(matches: & clap::ArgMatches, name: & str, def: usize)
+    -> Result<usize, FgError> {
+    Ok(parse_arg(matches, name, "a whole number")?.unwrap_or(def))
 }
\ No newline at end of file
@@ -0,0 +1,104 @@
+use std::fs::File;
+use std::io::{Error, Write};
+
+use super::color::Rgb;
+use super::figure::{Figure, Item};
+use super::png;
+use super::svg::num;
+
+/// Ratio of the distance between a Bézier control point and the end of a
+/// quarter of circle, to the radius.
+const KAPPA: f64 = 0.552_284_749_8;
+
+/// Writes the still drawing fig in file, as a PDF document of one page, and
+/// returns the number of bytes written. The page is the size of the drawing,
+/// a pixel being a point.
+/// see https://opensource.adobe.com/dc-acrobat-sdk-docs/pdfstandards/PDF32000_2008.pdf
+pub fn write_figure(fig: &Figure, mut file: File) -> Result<usize, Error> {
+    let content = png::zlib(page_content(fig).as_bytes());
+
+    let objects = [
+        String::from("<< /Type /Catalog /Pages 2 0 R >>").into_bytes(),
+        String::from("<< /Type /Pages /Kids [3 0 R] /Count 1 >>").into_bytes(),
+        format!("<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] /Contents 4 0 R \
+            /Resources << >> >>", fig.width, fig.height).into_bytes(),
+        [format!("<< /Length {} /Filter /FlateDecode >>\nstream\n", content.len()).into_bytes(),
+         content,
+         b"\nendstream".to_vec()].concat(),
+    ];
+
+    // the header, with a comment of binary bytes as advised
+    let mut pdf = b"%PDF-1.4\n%\xe2\xe3\xcf\xd3\n".to_vec();
+    let mut positions = Vec::with_capacity(objects.len());
+    for (i, object) in objects.iter().enumerate() {
+        positions.push(pdf.len());
+        pdf.extend_from_slice(format!("{} 0 obj\n", i + 1).as_bytes());
+        pdf.extend_from_slice(object);
+        pdf.extend_from_slice(b"\nendobj\n");
+    }
+
+    // each entry of the cross-reference table is 20 bytes long
+    let xref = pdf.len();
+    pdf.extend_from_slice(format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1)
+        .as_bytes());
+    for p in positions {
+        pdf.extend_from_slice(format!("{:010} 00000 n \n", p).as_bytes());
+    }
+    pdf.extend_from_slice(format!("trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+        objects.len() + 1, xref).as_bytes());
+
+    file.write_all(&pdf)?;
+    Ok(pdf.len())
+}
+
+/* Returns the content stream drawing fig. Its y axis is flipped first, so that
+ * the pixel positions of the figure can be used as they are. */
+fn page_content(fig: &Figure) -> String {
+    let point = |p: (f64, f64)| format!("{} {}", num(p.0, 2), num(p.1, 2));
+
+    let mut s = format!("1 0 0 -1 0 {} cm\n", fig.height);
+    // round caps and joins
+    s += "1 J 1 j\n";
+    s += &format!("{} rg 0 0 {} {} re f\n", rgb(fig.background), fig.width, fig.height);
+    for item in fig.items.iter() {
+        match item {
+            Item::Curve { start, curves, color, width } => {
+                s += &format!("{} RG {} w {} m\n", rgb(*color), num(*width, 2), point(*start));
+                for c in curves.iter() {
+                    s += &format!("{} {} {} c\n", point(c[0]), point(c[1]), point(c[2]));
+                }
+                s += "S\n";
+            }
+            Item::Polyline { points, color, width } => {
+                if points.is_empty() { continue }
+                s += &format!("{} RG {} w {} m\n", rgb(*color), num(*width, 2), point(points[0]));
+                for &p in points[1..].iter() {
+                    s += &format!("{} l\n", point(p));
+                }
+                s += "S\n";
+            }
+            Item::Disc { center, radius, color } => {
+                // four quarters of circle, from the rightmost point
+                let (x, y, r) = (center.0, center.1, *radius);
+                let k = KAPPA * r;
+                s += &format!("{} rg {} m\n", rgb(*color), point((x + r, y)));
+                s += &format!("{} {} {} c\n", point((x + r, y + k)), point((x + k, y + r)),
+                    point((x, y + r)));
+                s += &format!("{} {} {} c\n", point((x - k, y + r)), point((x - r, y + k)),
+                    point((x - r, y)));
+                s += &format!("{} {} {} c\n", point((x - r, y - k)), point((x - k, y - r)),
+                    point((x, y - r)));
+                s += &format!("{} {} {} c\n", point((x + k, y - r)), point((x + r, y - k)),
+                    point((x + r, y)));
+                s += "f\n";
+            }
+        }
+    }
+    s
+}
+
+/* Returns the colour c as the operands of the `rg` and `RG` operators */
+fn rgb(c: Rgb) -> String {
+    let f = |v: u8| num(v as f64 / 255.0, 3);
+    format!("{} {} {}", f(c.0), f(c.1), f(c.2))
+}
@@ -0,0 +1,395 @@
+use std::io::{self, Write};
+
+/// PNG file signature
+pub const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
+
+/// Writes the chunk of type name holding data, and returns its size.
+/// see https://www.w3.org/TR/png/#5Chunk-layout
+pub fn write_chunk<W: Write>(w: &mut W, name: &[u8; 4], data: &[u8]) -> io::Result<usize> {
+    w.write_all(&(data.len() as u32).to_be_bytes())?;
+    w.write_all(name)?;
+    w.write_all(data)?;
+    w.write_all(&chunk_crc(name, data).to_be_bytes())?;
+    Ok(data.len() + 12)
+}
+
+/// Returns the CRC of a chunk, computed over its type and its data.
+pub fn chunk_crc(name: &[u8; 4], data: &[u8]) -> u32 {
+    !crc32_update(crc32_update(0xFFFF_FFFF, name), data)
+}
+
+/* CRC-32 of the PNG specification, computed bit by bit (the images are small
+ * enough for the table to be unneeded). */
+fn crc32_update(mut crc: u32, data: &[u8]) -> u32 {
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 { 0xEDB8_8320 ^ (crc >> 1) } else { crc >> 1 };
+        }
+    }
+    crc
+}
+
+/// Returns the data of the IHDR chunk of a 8 bits RGB image.
+pub fn ihdr(width: usize, height: usize) -> Vec<u8> {
+    let mut data = Vec::with_capacity(13);
+    data.extend_from_slice(&(width as u32).to_be_bytes());
+    data.extend_from_slice(&(height as u32).to_be_bytes());
+    // bit depth, colour type (RGB), compression, filter, interlace
+    data.extend_from_slice(&[8, 2, 0, 0, 0]);
+    data
+}
+
+/// Returns the compressed image data of the RGB image rgb (3 bytes per pixel,
+/// row after row), as expected in IDAT chunks.
+pub fn image_data(rgb: &[u8], width: usize, height: usize) -> Vec<u8> {
+    let stride = 3 * width;
+    let mut filtered = Vec::with_capacity((stride + 1) * height);
+    let mut candidate = vec![0; stride];
+    for y in 0..height {
+        let row = &rgb[y*stride..(y+1)*stride];
+        let up = if y > 0 { Some(&rgb[(y-1)*stride..y*stride]) } else { None };
+
+        // keeps the filter whose output is the closest to 0, which usually
+        // compresses best
+        let mut best = (u64::MAX, 0, Vec::new());
+        for filter in 0..3u8 {
+            for i in 0..stride {
+                let left = if i >= 3 { row[i-3] } else { 0 };
+                let above = up.map_or(0, |u| u[i]);
+                candidate[i] = match filter {
+                    1 => row[i].wrapping_sub(left),
+                    2 => row[i].wrapping_sub(above),
+                    _ => row[i],
+                };
+            }
+            let cost = candidate.iter().map(|&b| (b as i8).unsigned_abs() as u64).sum();
+            if cost < best.0 {
+                best = (cost, filter, candidate.clone());
+            }
+        }
+        filtered.push(best.1);
+        filtered.extend_from_slice(&best.2);
+    }
+    zlib(&filtered)
+}
+
+/// Compresses data in the zlib format, with a fixed Huffman code.
+/// see https://www.rfc-editor.org/rfc/rfc1950 and rfc1951
+pub fn zlib(data: &[u8]) -> Vec<u8> {
+    let mut out = BitWriter::new();
+    // deflate, 32K window, no dictionary, check bits
+    out.bytes.extend_from_slice(&[0x78, 0x01]);
+    deflate(data, &mut out);
+    out.align();
+    out.bytes.extend_from_slice(&adler32(data).to_be_bytes());
+    out.bytes
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for chunk in data.chunks(5552) {
+        for &byte in chunk {
+            a += byte as u32;
+            b += a;
+        }
+        a %= 65521;
+        b %= 65521;
+    }
+    (b << 16) | a
+}
+
+/// Writes bits from the least significant one, as deflate expects.
+struct BitWriter {
+    bytes: Vec<u8>,
+    acc: u32,
+    n_bits: u32,
+}
+
+impl BitWriter {
+    fn new() -> BitWriter {
+        BitWriter { bytes: Vec::new(), acc: 0, n_bits: 0 }
+    }
+
+    /// Writes the n lowest bits of value.
+    fn bits(&mut self, value: u32, n: u32) {
+        self.acc |= value << self.n_bits;
+        self.n_bits += n;
+        while self.n_bits >= 8 {
+            self.bytes.push(self.acc as u8);
+            self.acc >>= 8;
+            self.n_bits -= 8;
+        }
+    }
+
+    /// Writes a Huffman code, which is stored from its most significant bit.
+    fn code(&mut self, code: u32, len: u32) {
+        let mut reversed = 0;
+        for i in 0..len {
+            reversed |= ((code >> i) & 1) << (len - 1 - i);
+        }
+        self.bits(reversed, len);
+    }
+
+    fn align(&mut self) {
+        if self.n_bits > 0 {
+            self.bits(0, 8 - self.n_bits);
+        }
+    }
+}
+
+const WINDOW: usize = 32768;
+const MIN_MATCH: usize = 3;
+const MAX_MATCH: usize = 258;
+/// Number of previous occurences of a prefix that are tried
+const MAX_CHAIN: usize = 64;
+const HASH_BITS: u32 = 15;
+
+/* Base values and extra bits of the length codes 257..285, and of the
+ * distance codes 0..29 */
+const LENGTH_BASE: [u16; 29] = [3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27,
+    31, 35, 43, 51, 59, 67, 83, 99, 115, 131, 163, 195, 227, 258];
+const LENGTH_EXTRA: [u8; 29] = [0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3,
+    3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0];
+const DIST_BASE: [u16; 30] = [1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129,
+    193, 257, 385, 513, 769, 1025, 1537, 2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577];
+const DIST_EXTRA: [u8; 30] = [0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7,
+    8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13, 13];
+
+/* Writes data as a single deflate block, compressed with the fixed Huffman
+ * code, matches being found through hash chains. */
+fn deflate(data: &[u8], out: &mut BitWriter) {
+    // last block, fixed Huffman code
+    out.bits(1, 1);
+    out.bits(1, 2);
+
+    let hash = |i: usize| {
+        let v = (data[i] as u32) << 16 | (data[i+1] as u32) << 8 | data[i+2] as u32;
+        (v.wrapping_mul(2_654_435_761) >> (32 - HASH_BITS)) as usize
+    };
+    let mut head = vec![usize::MAX; 1 << HASH_BITS];
+    let mut prev = vec![usize::MAX; WINDOW];
+    let insert = |i: usize, head: &mut Vec<usize>, prev: &mut Vec<usize>| {
+        if i + MIN_MATCH <= data.len() {
+            let h = hash(i);
+            prev[i % WINDOW] = head[h];
+            head[h] = i;
+        }
+    };
+
+    let mut i = 0;
+    while i < data.len() {
+        let (mut best_len, mut best_dist) = (0, 0);
+        if i + MIN_MATCH <= data.len() {
+            let max_len = MAX_MATCH.min(data.len() - i);
+            let mut candidate = head[hash(i)];
+            let mut tries = 0;
+            while candidate != usize::MAX && i - candidate <= WINDOW && tries < MAX_CHAIN {
+                let mut len = 0;
+                while len < max_len && data[candidate + len] == data[i + len] {
+                    len += 1;
+                }
+                if len > best_len {
+                    best_len = len;
+                    best_dist = i - candidate;
+                    if len == max_len { break }
+                }
+                let next = prev[candidate % WINDOW];
+                // the chain may have been overwritten by a newer position
+                if next == usize::MAX || next >= candidate { break }
+                candidate = next;
+                tries += 1;
+            }
+        }
+
+        if best_len >= MIN_MATCH {
+            write_match(best_len, best_dist, out);
+            for j in i..i + best_len {
+                insert(j, &mut head, &mut prev);
+            }
+            i += best_len;
+        } else {
+            write_literal(data[i] as u32, out);
+            insert(i, &mut head, &mut prev);
+            i += 1;
+        }
+    }
+    // end of block
+    write_literal(256, out);
+}
+
+/* Writes the literal/length symbol s with the fixed Huffman code */
+fn write_literal(s: u32, out: &mut BitWriter) {
+    match s {
+        0..=143   => out.code(0x30 + s, 8),
+        144..=255 => out.code(0x190 + s - 144, 9),
+        256..=279 => out.code(s - 256, 7),
+        _         => out.code(0xC0 + s - 280, 8),
+    }
+}
+
+fn write_match(len: usize, dist: usize, out: &mut BitWriter) {
+    let l = LENGTH_BASE.iter().rposition(|&b| b as usize <= len).unwrap();
+    write_literal(257 + l as u32, out);
+    out.bits((len - LENGTH_BASE[l] as usize) as u32, LENGTH_EXTRA[l] as u32);
+
+    let d = DIST_BASE.iter().rposition(|&b| b as usize <= dist).unwrap();
+    out.code(d as u32, 5);
+    out.bits((dist - DIST_BASE[d] as usize) as u32, DIST_EXTRA[d] as u32);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /* Reads bits from the least significant one, as BitWriter writes them. */
+    struct BitReader<'a> {
+        bytes: &'a [u8],
+        pos: usize,
+    }
+
+    impl<'a> BitReader<'a> {
+        fn bits(&mut self, n: u32) -> u32 {
+            let mut value = 0;
+            for i in 0..n {
+                let bit = (self.bytes[self.pos / 8] >> (self.pos % 8)) & 1;
+                value |= (bit as u32) << i;
+                self.pos += 1;
+            }
+            value
+        }
+
+        /* Reads a Huffman code of n bits, stored from its most significant bit,
+         * after the prefix code already read. */
+        fn code(&mut self, prefix: u32, n: u32) -> u32 {
+            (0..n).fold(prefix, |code, _| code << 1 | self.bits(1))
+        }
+
+        /* Reads a literal/length symbol of the fixed Huffman code. */
+        fn literal(&mut self) -> u32 {
+            let code = self.code(0, 7);
+            if code <= 0x17 { return 256 + code }
+            let code = self.code(code, 1);
+            match code {
+                0x30..=0xBF => code - 0x30,
+                0xC0..=0xC7 => 280 + code - 0xC0,
+                _           => 144 + self.code(code, 1) - 0x190,
+            }
+        }
+    }
+
+    /* Decompresses the zlib stream z, that may hold stored and fixed Huffman
+     * blocks, and checks its header and its Adler-32. */
+    fn inflate(z: &[u8]) -> Vec<u8> {
+        assert_eq!(z[0] & 0x0F, 8, "not deflate");
+        assert_eq!((z[0] as u32 * 256 + z[1] as u32) % 31, 0, "bad check bits");
+        let mut r = BitReader { bytes: &z[2..], pos: 0 };
+        let mut out: Vec<u8> = Vec::new();
+        loop {
+            let last = r.bits(1);
+            match r.bits(2) {
+                0 => {
+                    r.pos = r.pos.div_ceil(8) * 8;
+                    let len = r.bits(16);
+                    assert_eq!(r.bits(16), !len & 0xFFFF);
+                    for _ in 0..len {
+                        out.push(r.bits(8) as u8);
+                    }
+                }
+                1 => loop {
+                    let s = r.literal();
+                    if s < 256 {
+                        out.push(s as u8);
+                        continue
+                    }
+                    if s == 256 { break }
+                    let l = s as usize - 257;
+                    let len = LENGTH_BASE[l] as usize + r.bits(LENGTH_EXTRA[l] as u32) as usize;
+                    let d = r.code(0, 5) as usize;
+                    let dist = DIST_BASE[d] as usize + r.bits(DIST_EXTRA[d] as u32) as usize;
+                    assert!(dist <= out.len() && dist <= WINDOW, "distance too far");
+                    for _ in 0..len {
+                        out.push(out[out.len() - dist]);
+                    }
+                }
+                t => panic!("unexpected block type {}", t),
+            }
+            if last == 1 { break }
+        }
+        let end = 2 + r.pos.div_ceil(8);
+        assert_eq!(z.len(), end + 4, "trailing bytes");
+        assert_eq!(z[end..].to_vec(), adler32(&out).to_be_bytes().to_vec());
+        out
+    }
+
+    #[test]
+    fn crc_and_adler_check_values() {
+        assert_eq!(!crc32_update(0xFFFF_FFFF, b"123456789"), 0xCBF4_3926);
+        assert_eq!(chunk_crc(b"IEND", &[]), 0xAE42_6082);
+        assert_eq!(adler32(b"Wikipedia"), 0x11E6_0398);
+        assert_eq!(adler32(&[0xFF; 100_000]), {
+            // computed without the deferred modulo
+            let (mut a, mut b) = (1u64, 0u64);
+            for _ in 0..100_000 {
+                a = (a + 0xFF) % 65521;
+                b = (b + a) % 65521;
+            }
+            (b << 16 | a) as u32
+        });
+    }
+
+    #[test]
+    fn inflate_reads_a_stored_block() {
+        let z = [0x78, 0x01, 0x01, 0x03, 0x00, 0xFC, 0xFF, b'a', b'b', b'c',
+                 0x02, 0x4D, 0x01, 0x27];
+        assert_eq!(inflate(&z), b"abc");
+    }
+
+    #[test]
+    fn zlib_round_trips() {
+        let mut noise = Vec::with_capacity(100_000);
+        let mut x: u32 = 12345;
+        for _ in 0..100_000 {
+            x = x.wrapping_mul(1_103_515_245).wrapping_add(12345);
+            noise.push((x >> 16) as u8);
+        }
+        // repeats further than the window, and up to the longest match
+        let mut far: Vec<u8> = noise[..40_000].to_vec();
+        far.extend_from_slice(&noise[..1000]);
+        let cases: Vec<Vec<u8>> = vec![
+            Vec::new(),
+            b"a".to_vec(),
+            b"abcabcabcabcabcabcabcabcabcabc, all of 0123456789 and \xFF\x90\x8F".to_vec(),
+            vec![7; 1000],
+            (0..=255).cycle().take(5000).collect(),
+            noise,
+            far,
+        ];
+        for data in cases.iter() {
+            assert_eq!(&inflate(&zlib(data)), data);
+        }
+    }
+
+    #[test]
+    fn image_data_inflates_to_filtered_rows() {
+        let (w, h) = (5, 4);
+        let rgb: Vec<u8> = (0..3 * w * h).map(|i| (i * 37 % 251) as u8).collect();
+        let rows = inflate(&image_data(&rgb, w, h));
+        assert_eq!(rows.len(), (3 * w + 1) * h);
+        // undoes the filters
+        let mut image: Vec<u8> = Vec::new();
+        for (y, row) in rows.chunks(3 * w + 1).enumerate() {
+            for i in 0..3 * w {
+                let left = if i >= 3 { image[y * 3 * w + i - 3] } else { 0 };
+                let above = if y > 0 { image[(y - 1) * 3 * w + i] } else { 0 };
+                image.push(match row[0] {
+                    0 => row[1 + i],
+                    1 => row[1 + i].wrapping_add(left),
+                    2 => row[1 + i].wrapping_add(above),
+                    f => panic!("unexpected filter {}", f),
+                });
+            }
+        }
+        assert_eq!(image, rgb);
+    }
+}
@@ -6,6 +6,13 @@ use std::num::ParseFloatError;
 
 use super::fourier::CoeffsSet;
 use super::complex::Complex;
+use super::json::{self, Json};
+use super::svgpath;
+use super::spline::Boundary;
+
+/// Highest rank of a harmonic that coefficient files may hold, as the set is
+/// allocated up to it.
+pub const MAX_HARMONIC: usize = 1 << 20;
 
 /// Represents error that could happen when reading files
 pub enum ReadingError {
@@ -13,6 +20,12 @@ pub enum ReadingError {
     FileStreamError(std::io::Error, String),
     NotEnoughPoints,
     IllFormedCoeffs,
+    JsonError(String),
+    PathError(String),
+    ColumnsError(String),
+    BoundaryError(String),
+    /// Ill-formed line of points, cf `PointsSet::from_str`
+    IllFormedPoint { line: usize, column: usize, text: String, message: &'static str },
 }
 
 impl fmt::Display for ReadingError {
@@ -26,6 +39,17 @@ impl fmt::Display for ReadingError {
                 { write!(f, "Not enough points !")                         }
             ReadingError::IllFormedCoeffs =>
                 { write!(f, "Specified file is ill-formed !")              }
+            ReadingError::JsonError(e) =>
+                { write!(f, "JSON error: {}", e)                           }
+            ReadingError::PathError(e) =>
+                { write!(f, "SVG path error: {}", e)                       }
+            ReadingError::ColumnsError(e) =>
+                { write!(f, "Columns error: {}", e)                        }
+            ReadingError::BoundaryError(e) =>
+                { write!(f, "Boundary error: {}", e)                       }
+            ReadingError::IllFormedPoint { line, column, text, message } =>
+                { write!(f, "line {}, column {}: {} in `{}`", line, column, message,
+                          text)                                            }
         }
     }
 }
@@ -48,13 +72,20 @@ struct Point {
 /// Holds a set of point to be interpolated
 /// Each point has coordinates `x`, `y`, a timestamp `t` and a "method" boolean 
 /// that will be `true` if the interpolation method after that point is 
-/// linear.
+/// linear. The boundary of the spline through them may be given with them.
 #[allow(dead_code)]
 pub struct PointsSet {
     pub xx: Vec<f64>,
     pub yy: Vec<f64>,
     pub tt: Vec<f64>,
-    pub mm: Vec<bool>
+    pub mm: Vec<bool>,
+    pub boundary: Option<Boundary>,
+}
+
+/// Error found while parsing a Point, at the given column (from 1).
+struct PointError {
+    column: usize,
+    message: &'static str,
 }
 
 /// Parses a Point, the data should be formatted as:
@@ -67,36 +98,63 @@ pub struct PointsSet {
 /// linear if `type == "l"`, and a cubic spline otherwise. In particular, we 
 /// will use `"s"` for the clarity, but not providing it will also work.
 impl FromStr for Point {
-    type Err = ParseFloatError;
-
-    fn from_str(s: &str) -> Result<Point, ParseFloatError> {
-        let parts: Vec<&str> = s.split(':')
-                                .collect();
-        let parsed_t = parts[0].trim()
-                               .parse::<f64>()?;
-
-        let right_parts: Vec<&str> = parts[1].trim().split('(').collect();
-        let is_line = if right_parts[0].starts_with("l") {true} else {false};
-        let coords: Vec<&str> = right_parts[1].trim()
-                    .trim_end_matches(|c| c == ')')
-                    .split(',')
-                    .collect();
-        let parsed_x: f64 = coords[0].trim().parse::<f64>()?;
-        let parsed_y: f64 = coords[1].trim().parse::<f64>()?;
-
-        Ok(Point{x: parsed_x, y: parsed_y, t: parsed_t, is_line: is_line})
+    type Err = PointError;
+
+    fn from_str(s: &str) -> Result<Point, PointError> {
+        // errors point at the byte `at` of s
+        let error = |at: usize, message| PointError { column: s[..at].chars().count() + 1,
+                                                      message };
+        // the number in s[from..to], pointed at its first character
+        let number = |from: usize, to: usize, message| {
+            let field = &s[from..to];
+            let at = from + field.len() - field.trim_start().len();
+            field.trim().parse::<f64>().map_err(|_| error(at, message))
+        };
+
+        // without `:`, the time is the first word, and the colon was expected after it
+        let colon = s.find(':').ok_or_else(|| {
+            let start = s.len() - s.trim_start().len();
+            error(s[start..].find(|c: char| c.is_whitespace() || c == '(')
+                            .map_or(s.len(), |i| start + i), "expected `:` after the time")
+        })?;
+        let parsed_t = number(0, colon, "ill-formed time")?;
+
+        let open = s[colon..].find('(').map(|i| colon + i)
+                             .ok_or_else(|| error(s.len(), "expected `(` before the coordinates"))?;
+        let is_line = s[colon + 1..open].trim().starts_with("l");
+        let close = s[open..].find(')').map(|i| open + i)
+                             .ok_or_else(|| error(s.len(), "expected `)` after the coordinates"))?;
+        let comma = s[open..close].find(',').map(|i| open + i)
+                                  .ok_or_else(|| error(close, "expected `,` between the \
+                                                                coordinates"))?;
+        let parsed_x = number(open + 1, comma, "ill-formed x coordinate")?;
+        let parsed_y = number(comma + 1, close, "ill-formed y coordinate")?;
+
+        let rest = &s[close + 1..];
+        if !rest.trim().is_empty() {
+            return Err(error(close + 1 + rest.len() - rest.trim_start().len(),
+                "unexpected text after the point"))
+        }
+        Ok(Point{x: parsed_x, y: parsed_y, t: parsed_t, is_line})
     } 
 }
 
 /// Parses a set of points, should be formatted with one point per line (cf 
-/// Point::FromStr)
+/// Point::FromStr). Empty lines are skipped, and so is what follows a `#`, as
+/// comments. Lines may end with `\r\n`.
+/// A `# boundary: natural` line gives the boundary of the spline through the
+/// points, cf `Boundary::from_str`.
 impl FromStr for PointsSet {
     type Err = ReadingError;
 
     fn from_str(s: &str) -> Result<PointsSet, ReadingError> {
-        let points_data: Vec<&str> = s.split('\n')
-                                      .filter(|s| !s.is_empty())
-                                      .collect();
+        let boundary = boundary_comment(s)?;
+
+        // numbered from 1, without their comment
+        let points_data: Vec<(usize, &str)> = s.lines().enumerate()
+            .map(|(i, l)| (i + 1, l.split('#').next().unwrap().trim_end()))
+            .filter(|(_, l)| !l.trim_start().is_empty())
+            .collect();
         if points_data.len() < 2 {
             return Err(ReadingError::NotEnoughPoints)
         }
@@ -106,7 +164,7 @@ impl FromStr for PointsSet {
         let mut parsed_tt = Vec::<f64>::new();
         let mut parsed_mm = Vec::<bool>::new();
 
-        for point_data in points_data {
+        for (line, point_data) in points_data {
             let p = Point::from_str(point_data);
             match p {
                 Ok(point) => {
@@ -115,7 +173,12 @@ impl FromStr for PointsSet {
                     parsed_tt.push(point.t);
                     parsed_mm.push(point.is_line);
                 }
-                Err(e) => return Err(ReadingError::ParseError(e))
+                Err(e) => return Err(ReadingError::IllFormedPoint {
+                    line,
+                    column: e.column,
+                    text: String::from(point_data),
+                    message: e.message,
+                })
             }
         }
 
@@ -123,11 +186,39 @@ impl FromStr for PointsSet {
             xx: parsed_xx,
             yy: parsed_yy, 
             tt: parsed_tt,
-            mm: parsed_mm
+            mm: parsed_mm,
+            boundary,
         })
     }
 }
 
+/* Returns the boundary given by the last `# boundary: ...` line of s, if
+ * any, cf `Boundary::from_str`. */
+fn boundary_comment(s: &str) -> Result<Option<Boundary>, ReadingError> {
+    let mut boundary = None;
+    for line in s.lines().filter_map(|l| l.trim().strip_prefix('#')) {
+        if let Some(b) = line.trim().strip_prefix("boundary:") {
+            boundary = Some(b.parse::<Boundary>()?);
+        }
+    }
+    Ok(boundary)
+}
+
+impl PointsSet {
+    /// Returns the points as complex numbers x + j * y.
+    pub fn points(&self) -> Vec<Complex> {
+        self.xx.iter().zip(self.yy.iter())
+               .map(|(&x, &y)| Complex { re: x, im: y })
+               .collect()
+    }
+}
+
+impl std::convert::From<json::Error> for ReadingError {
+    fn from(e: json::Error) -> ReadingError {
+        ReadingError::JsonError(e.to_string())
+    }
+}
+
 impl std::convert::From<ParseFloatError> for ReadingError {
     fn from(e: ParseFloatError) -> ReadingError {
         ReadingError::ParseError(e)
@@ -141,8 +232,11 @@ impl FromStr for Complex {
     type Err = ReadingError;
 
     fn from_str(s: &str) -> Result<Complex, ReadingError> {
-        let parts: Vec<&str> = s.trim_matches( |c| c == '(' || c == ')' )
+        let parts: Vec<&str> = s.trim().trim_matches( |c| c == '(' || c == ')' )
                                 .split(',').collect();
+        if parts.len() != 2 {
+            return Err(ReadingError::IllFormedCoeffs)
+        }
         let parsed_re: f64 = parts[0].trim().parse::<f64>()?;
         let parsed_im = parts[1].trim().parse::<f64>()?;
         Ok(Complex {
@@ -151,58 +245,384 @@ impl FromStr for Complex {
     }
 }
 
-/// Parses a set of (Fourier) coefficient, i.e. an ensemble of complex numbers
-/// The data should be n lines formatted as:
-/// `<Complex_x> & <Complex_y>`
-/// Where the line number indicates the rank of the coefficient (i-th line -> 
-/// i-th harmonic), `Complex_x` is the coefficient for the x(t) function and 
-/// `Complex_y` for the y(t) function.
+/// Parses the boundary of the spline through the points of a drawing,
+/// formatted as `periodic`, `natural`, `not-a-knot`, `quadratic`, or
+/// `clamped (x, y) (x, y)` with the slopes of the drawing at its start and at
+/// its end.
+impl FromStr for Boundary {
+    type Err = ReadingError;
+
+    fn from_str(s: &str) -> Result<Boundary, ReadingError> {
+        let error = |m: String| ReadingError::BoundaryError(m);
+        let s = s.trim();
+        let (name, rest) = s.split_at(s.find(|c: char| c.is_whitespace() || c == '(')
+                                       .unwrap_or(s.len()));
+        match name {
+            "periodic"   => return Ok(Boundary::Periodic),
+            "natural"    => return Ok(Boundary::Natural),
+            "not-a-knot" => return Ok(Boundary::NotAKnot),
+            "quadratic"  => return Ok(Boundary::Quadratic),
+            "clamped"    => {}
+            _ => return Err(error(format!("unknown boundary `{}`", s))),
+        }
+
+        let mut slopes = Vec::new();
+        for pair in rest.split(')').map(|p| p.trim()).filter(|p| !p.is_empty()) {
+            let values: Vec<Option<f64>> = pair.strip_prefix('(').unwrap_or("")
+                .split(',').map(|v| v.trim().parse::<f64>().ok()).collect();
+            match values[..] {
+                [Some(x), Some(y)] => slopes.push(vec![x, y]),
+                _ => return Err(error(format!("`{})` is not a slope `(x, y)`", pair))),
+            }
+        }
+        if slopes.len() != 2 {
+            return Err(error(String::from("clamped takes the slopes at the start and at the \
+                                           end, as `clamped (x, y) (x, y)`")))
+        }
+        Ok(Boundary::Clamped(slopes.remove(0), slopes.remove(0)))
+    }
+}
+
+/// Optional information written above Fourier coefficients, as
+/// `# key: value` lines.
+/// Members:
+///
+/// - `period`  : period of the drawing, in its time unit (`# period: 14`)
+/// - `origin`  : time at which the drawing starts (`# origin: 0`)
+/// - `terms`   : number of coefficient lines that follow (`# terms: 6`)
+/// - `source`  : file the coefficients were computed from
+///               (`# source: points.txt`)
+#[allow(clippy::doc_overindented_list_items)]
+#[derive(Clone, Debug, Default)]
+pub struct CoeffsHeader {
+    pub period: Option<f64>,
+    pub origin: Option<f64>,
+    pub terms: Option<usize>,
+    pub source: Option<String>,
+}
+
+impl CoeffsHeader {
+    /// Returns the time span of the drawing, 0 to 2 pi if it is not known.
+    pub fn t_span(&self) -> (f64, f64) {
+        let origin = self.origin.unwrap_or(0.0);
+        (origin, origin + self.period.unwrap_or(2.0 * std::f64::consts::PI))
+    }
+
+    /* Reads the `key: value` line s, unknown keys being ignored */
+    fn parse_line(&mut self, s: &str) -> Result<(), ReadingError> {
+        let parts: Vec<&str> = s.splitn(2, ':').collect();
+        if parts.len() < 2 {   return Ok(())   }
+        let value = parts[1].trim();
+        match parts[0].trim() {
+            "period" => self.period = Some(value.parse::<f64>()?),
+            "origin" => self.origin = Some(value.parse::<f64>()?),
+            "terms"  => self.terms = Some(value.parse::<usize>()
+                                               .map_err(|_| ReadingError::IllFormedCoeffs)?),
+            "source" => self.source = Some(String::from(value)),
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+/// Parses a set of (Fourier) coefficients and its header. The coefficients
+/// are lines formatted as:
+/// `k: <Complex_x> & <Complex_y>`
+/// Where `k` is the rank of the coefficient, `Complex_x` is the coefficient
+/// c_k and `Complex_y` the coefficient c_-k. `k:` can be left out, the line
+/// being then the one after the previous one (the first one being c_0), so
+/// that the i-th line holds the i-th harmonic. The missing harmonics are 0,
+/// which allows sparse sets. Ranks go up to `MAX_HARMONIC`.
+/// Lines starting with `#` are the header (cf `CoeffsHeader`), or comments.
+pub fn parse_coeffs(s: &str) -> Result<(CoeffsSet, CoeffsHeader), ReadingError> {
+    let mut header = CoeffsHeader::default();
+    let mut read: Vec<(usize, Complex, Complex)> = Vec::new();
+
+    for line in s.lines().map(|l| l.trim()).filter(|l| !l.is_empty()) {
+        if let Some(comment) = line.strip_prefix('#') {
+            header.parse_line(comment)?;
+            continue
+        }
+        let (k, line) = match line.find(':') {
+            Some(i) => (line[..i].trim().parse::<usize>().ok(), &line[i+1..]),
+            None    => (read.last().map_or(Some(0), |&(k, _, _)| k.checked_add(1)), line),
+        };
+        let k = k.filter(|&k| k <= MAX_HARMONIC).ok_or(ReadingError::IllFormedCoeffs)?;
+        let parts: Vec<&str> = line.split('&')
+                                   .filter(|s| !s.is_empty())
+                                   .collect();
+        if parts.len() < 2 || read.iter().any(|&(i, _, _)| i == k) {
+            return Err(ReadingError::IllFormedCoeffs)
+        }
+        read.push((k, Complex::from_str(parts[0])?, Complex::from_str(parts[1])?));
+    }
+    if header.terms.is_some_and(|n| n != read.len()) {
+        return Err(ReadingError::IllFormedCoeffs)
+    }
+
+    let n = read.iter().map(|&(k, _, _)| k + 1).max().unwrap_or(0);
+    let mut set = CoeffsSet::new(n);
+    for (k, cp, cn) in read {
+        set.ppos[k] = cp;
+        set.nneg[k] = cn;
+    }
+    Ok((set, header))
+}
+
+/// Parses a set of (Fourier) coefficients, cf `parse_coeffs`, leaving its
+/// header out.
 impl FromStr for CoeffsSet {
     type Err = ReadingError;
 
     fn from_str(s: &str) -> Result<CoeffsSet, ReadingError> {
-        let lines: Vec<&str> =  s.split('\n')
-        .filter(|s| !s.is_empty())
-        .collect();
+        Ok(parse_coeffs(s)?.0)
+    }
+}
 
-        let mut ppos = Vec::<Complex>::new();
-        let mut nneg = Vec::<Complex>::new();
-        for line in lines {
-            let parts: Vec<&str> =  line.split('&')
-                        .filter(|s| !s.is_empty())
-                        .collect();
-            if parts.len() < 2 {   return Err(ReadingError::IllFormedCoeffs)   }
-            let cp = Complex::from_str(parts[0])?;
-            let cn = Complex::from_str(parts[1])?;
-            ppos.push(cp);
-            nneg.push(cn);    
-        };
-        Ok(CoeffsSet{ppos: ppos, nneg: nneg})
+/// Format of the input files.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FileFormat {
+    /// One point or harmonic per line, cf `PointsSet` and `parse_coeffs`
+    Text,
+    /// cf `points_from_json` and `coeffs_from_json`
+    Json,
+    /// Paths of an SVG drawing, that only hold points, cf `svgpath::read_svg`
+    Svg,
+    /// Delimited numeric columns, that only hold points, cf `parse_columns`
+    Columns,
+}
+
+impl FileFormat {
+    /// Returns the format given by the extension of path: JSON for `.json`
+    /// files, SVG for `.svg` files, columns for `.mat`, `.csv`, `.tsv` and
+    /// `.dat` files, text otherwise.
+    pub fn from_path(path: &str) -> FileFormat {
+        let path = path.to_lowercase();
+        let columns = [".mat", ".csv", ".tsv", ".dat"];
+        if path.ends_with(".json")     {  FileFormat::Json     }
+        else if path.ends_with(".svg") {  FileFormat::Svg      }
+        else if columns.iter().any(|e| path.ends_with(e))
+                                       {  FileFormat::Columns  }
+        else                           {  FileFormat::Text     }
     }
 }
 
-/// Reads a file and return the set of points it contains. 
-///  RETURN       Result<PointsSet, ReadingError>
-#[allow(dead_code)]
-pub fn read_file(filename: & str) -> Result<PointsSet, ReadingError> {
-    let mut f = match File::open(filename) {
-        Err(e) => return Err(ReadingError::FileStreamError(e, String::from(filename))),
-        Ok(f) => f,
+/// Column of a delimited file, given by its position, from 0, or by its name
+/// in the header row.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Column {
+    Index(usize),
+    Name(String),
+}
+
+/// Columns holding the times, the coordinates and, optionally, the
+/// interpolation method after the points, cf `parse_columns`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ColumnMap {
+    pub t: Column,
+    pub x: Column,
+    pub y: Column,
+    pub mode: Option<Column>,
+}
+
+/// The `t x y` columns of `points.mat`, as `piaf.m` reads them.
+impl Default for ColumnMap {
+    fn default() -> ColumnMap {
+        ColumnMap { t: Column::Index(0), x: Column::Index(1), y: Column::Index(2), mode: None }
+    }
+}
+
+/// Parses a ColumnMap formatted as `t,x,y` or `t,x,y,mode`, each column being
+/// a position from 1, as Octave counts them, or a name of the header row.
+impl FromStr for ColumnMap {
+    type Err = ReadingError;
+
+    fn from_str(s: &str) -> Result<ColumnMap, ReadingError> {
+        let columns: Vec<Column> = s.split(',').map(|c| c.trim()).map(|c|
+            match c.parse::<usize>() {
+                Ok(0)  => Err(ReadingError::ColumnsError(String::from(
+                    "columns are numbered from 1"))),
+                Ok(i)  => Ok(Column::Index(i - 1)),
+                Err(_) => Ok(Column::Name(String::from(c))),
+            }).collect::<Result<_, _>>()?;
+        if columns.len() != 3 && columns.len() != 4 {
+            return Err(ReadingError::ColumnsError(format!(
+                "expected the columns `t,x,y` or `t,x,y,mode`, got `{}`", s)))
+        }
+        let mut columns = columns.into_iter();
+        Ok(ColumnMap { t: columns.next().unwrap(), x: columns.next().unwrap(),
+                       y: columns.next().unwrap(), mode: columns.next() })
+    }
+}
+
+/// Parses points written in delimited numeric columns, as `dlmread` reads
+/// them, the map giving the columns to use. Fields are separated by commas if
+/// any line has one, by spaces or tabs otherwise. The first row is a header if
+/// none of its fields is a number. Empty lines, and those starting with `#`
+/// or `%`, are skipped, but a `# boundary: natural` line, that gives the
+/// boundary of the spline through the points as for `PointsSet::from_str`.
+/// The interpolation after a point is linear if its mode is `l`, `line`, or a
+/// number that is not 0, and a cubic spline otherwise. Without a mode column
+/// in the map, the column named `mode` in the header is used, if any.
+pub fn parse_columns(s: &str, map: &ColumnMap) -> Result<PointsSet, ReadingError> {
+    let error = |s: String| ReadingError::ColumnsError(s);
+    let lines: Vec<(usize, &str)> = s.lines().enumerate()
+        .map(|(i, l)| (i + 1, l.trim()))
+        .filter(|(_, l)| !l.is_empty() && !l.starts_with('#') && !l.starts_with('%'))
+        .collect();
+    let comma = lines.iter().any(|(_, l)| l.contains(','));
+    let fields = |l| split_fields(l, comma);
+
+    let header: Option<Vec<&str>> = lines.first().map(|&(_, l)| fields(l))
+        .filter(|ff| ff.iter().all(|f| f.parse::<f64>().is_err()));
+    let index = |c: &Column| match (c, header.as_ref()) {
+        (&Column::Index(i), _) => Ok(i),
+        (Column::Name(name), Some(names)) => names.iter().position(|n| n == name)
+            .ok_or_else(|| error(format!("no column named `{}` in the header", name))),
+        (Column::Name(name), None) => Err(error(format!(
+            "no header row naming the column `{}`", name))),
+    };
+    let (it, ix, iy) = (index(&map.t)?, index(&map.x)?, index(&map.y)?);
+    let imode = match map.mode.as_ref() {
+        Some(c) => Some(index(c)?),
+        None    => header.as_ref().and_then(|names| names.iter().position(|&n| n == "mode")),
     };
 
-    let mut data = String::new();
-    if let  Err(e) = f.read_to_string(&mut data) {
-        return Err(ReadingError::FileStreamError(e, String::from(filename)));
+    let rows = &lines[if header.is_some() { 1 } else { 0 }..];
+    if rows.len() < 2 {
+        return Err(ReadingError::NotEnoughPoints)
+    }
+    let mut set = PointsSet { xx: Vec::new(), yy: Vec::new(), tt: Vec::new(), mm: Vec::new(),
+                              boundary: boundary_comment(s)? };
+    for &(n, line) in rows {
+        let ff = fields(line);
+        let field = |i: usize| ff.get(i).copied().ok_or_else(||
+            error(format!("line {}: no column {}", n, i + 1)));
+        let number = |i: usize| field(i)?.parse::<f64>().map_err(|_|
+            error(format!("line {}: `{}` is not a number", n, ff[i])));
+        set.tt.push(number(it)?);
+        set.xx.push(number(ix)?);
+        set.yy.push(number(iy)?);
+        set.mm.push(match imode {
+            None => false,
+            Some(i) => match field(i)? {
+                "l" | "line"          => true,
+                "" | "s" | "spline"   => false,
+                m => m.parse::<f64>().map(|m| m != 0.0).map_err(|_| error(format!(
+                    "line {}: `{}` is not an interpolation method", n, m)))?,
+            },
+        });
+    }
+    Ok(set)
+}
+
+/// Reads the points of a JSON document formatted as
+/// `{"points": [{"t": 0, "x": 40, "y": 0, "mode": "line"}, ...]}`
+/// where `mode`, the interpolation method after the point, is `"line"` or
+/// `"spline"`, which is the default. A `"boundary"` member may give the
+/// boundary of the spline, as `Boundary::from_str` reads it.
+pub fn points_from_json(doc: &Json) -> Result<PointsSet, ReadingError> {
+    let points = doc.get("points").and_then(|p| p.as_array())
+        .ok_or_else(|| ReadingError::JsonError(String::from("no `points` array")))?;
+    if points.len() < 2 {
+        return Err(ReadingError::NotEnoughPoints)
     }
 
-    let set = PointsSet::from_str(&mut data)?;
+    let boundary = match doc.get("boundary").map(|b| b.as_str()) {
+        None          => None,
+        Some(Some(b)) => Some(b.parse::<Boundary>()?),
+        Some(None)    => return Err(ReadingError::JsonError(String::from(
+            "the `boundary` is not a string"))),
+    };
+
+    let mut set = PointsSet { xx: Vec::new(), yy: Vec::new(), tt: Vec::new(), mm: Vec::new(),
+                              boundary };
+    for (i, point) in points.iter().enumerate() {
+        let number = |key: &str| point.get(key).and_then(|v| v.as_f64()).ok_or_else(||
+            ReadingError::JsonError(format!("no number `{}` in point {}", key, i)));
+        set.tt.push(number("t")?);
+        set.xx.push(number("x")?);
+        set.yy.push(number("y")?);
+        set.mm.push(match point.get("mode").map(|m| m.as_str()) {
+            None | Some(Some("spline")) => false,
+            Some(Some("line"))          => true,
+            _ => return Err(ReadingError::JsonError(format!(
+                "the `mode` of point {} is neither \"line\" nor \"spline\"", i))),
+        });
+    }
     Ok(set)
 }
 
-/// Reads Fourier coefficients written in the file named filename. 
-/// RETURN           Result<CoeffsSet, ReadingError>
-#[allow(dead_code)]
-pub fn read_fourier_coeffs (filename: &str) -> Result<CoeffsSet, ReadingError> {
+/// Reads the Fourier coefficients of a JSON document formatted as
+/// `{"period": 14, "origin": 0, "source": "points.txt",
+///   "terms": [{"k": -1, "re": 2.5, "im": 0, "magnitude": 2.5, "phase": 0}, ...]}`
+/// where each term is c_k, given by `re` and `im`, or else by `magnitude`
+/// and `phase` (in radians). The header members are optional, and so are the
+/// terms that are 0. `k` goes from `-MAX_HARMONIC` to `MAX_HARMONIC`.
+pub fn coeffs_from_json(doc: &Json) -> Result<(CoeffsSet, CoeffsHeader), ReadingError> {
+    let error = |s: String| ReadingError::JsonError(s);
+    let terms = doc.get("terms").and_then(|t| t.as_array())
+        .ok_or_else(|| error(String::from("no `terms` array")))?;
+
+    let mut read: Vec<(i64, Complex)> = Vec::with_capacity(terms.len());
+    for (i, term) in terms.iter().enumerate() {
+        let number = |key: &str| term.get(key).and_then(|v| v.as_f64());
+        let k = number("k").filter(|k| k.fract() == 0.0)
+            .ok_or_else(|| error(format!("no integer `k` in term {}", i)))?;
+        if k.abs() > MAX_HARMONIC as f64 {
+            return Err(error(format!("`k` of term {} is beyond {}", i, MAX_HARMONIC)))
+        }
+        let k = k as i64;
+        let c = match (number("re"), number("im"), number("magnitude"), number("phase")) {
+            (Some(re), Some(im), _, _) => Complex { re, im },
+            (_, _, Some(m), Some(phase)) => Complex::expj(phase) * m,
+            _ => return Err(error(format!("neither `re` and `im` nor `magnitude` and \
+                `phase` in term {}", i))),
+        };
+        if read.iter().any(|&(j, _)| j == k) {
+            return Err(error(format!("harmonic {} is given twice", k)))
+        }
+        read.push((k, c));
+    }
+
+    let n = read.iter().map(|&(k, _)| k.unsigned_abs() as usize + 1).max().unwrap_or(0);
+    let mut set = CoeffsSet::new(n);
+    for (k, c) in read {
+        if k >= 0 { set.ppos[k as usize] = c } else { set.nneg[(-k) as usize] = c }
+    }
+    let header = CoeffsHeader {
+        period: doc.get("period").and_then(|p| p.as_f64()),
+        origin: doc.get("origin").and_then(|o| o.as_f64()),
+        terms: None,
+        source: doc.get("source").and_then(|s| s.as_str()).map(String::from),
+    };
+    Ok((set, header))
+}
+
+/// What an input file holds.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Content {
+    /// Points to interpolate, cf `read_file`
+    Points,
+    /// Fourier coefficients, cf `read_fourier_coeffs`
+    Coeffs,
+}
+
+/// Tells what the file named filename, in the given format, holds: Fourier
+/// coefficients if its lines are complex numbers separated by `&`, or if it
+/// has `terms`, points otherwise.
+pub fn guess_content(filename: &str, format: FileFormat) -> Result<Content, ReadingError> {
+    let coeffs = match format {
+        FileFormat::Text => read_to_string(filename)?.lines().any(|l| l.contains('&')),
+        FileFormat::Json => json::parse(&read_to_string(filename)?)?.get("terms").is_some(),
+        FileFormat::Svg | FileFormat::Columns => false,
+    };
+    if coeffs {  Ok(Content::Coeffs)  }
+    else      {  Ok(Content::Points)  }
+}
+
+/// Reads the whole file named filename.
+pub fn read_to_string(filename: &str) -> Result<String, ReadingError> {
     let mut f = match File::open(filename) {
         Err(e) => return Err(ReadingError::FileStreamError(e, String::from(filename))),
         Ok(f) => f,
@@ -212,9 +632,49 @@ pub fn read_fourier_coeffs (filename: &str) -> Result<CoeffsSet, ReadingError> {
     if let  Err(e) = f.read_to_string(&mut data) {
         return Err(ReadingError::FileStreamError(e, String::from(filename)));
     }
+    Ok(data)
+}
 
-    let set = CoeffsSet::from_str(&mut data)?;
-    Ok(set)
+/// Reads a file in the given format and return the set of points it contains.
+/// Those of SVG paths are the ends of their segments, cf `svgpath::points`.
+///  RETURN       Result<PointsSet, ReadingError>
+#[allow(dead_code)]
+pub fn read_file(filename: & str, format: FileFormat) -> Result<PointsSet, ReadingError> {
+    match format {
+        FileFormat::Text => PointsSet::from_str(&read_to_string(filename)?),
+        FileFormat::Json => points_from_json(&json::parse(&read_to_string(filename)?)?),
+        FileFormat::Svg  => Ok(svgpath::points(&svgpath::read_svg(filename)?)),
+        FileFormat::Columns => read_columns(filename, &ColumnMap::default()),
+    }
+}
+
+/* Returns the fields of a line of columns, separated by commas or else by
+ * spaces and tabs */
+fn split_fields(line: &str, comma: bool) -> Vec<&str> {
+    if comma {  line.split(',').map(|f| f.trim()).collect()  }
+    else     {  line.split_whitespace().collect()            }
+}
+
+/// Reads the points written in the columns of map of the file named filename,
+/// cf `parse_columns`.
+pub fn read_columns(filename: &str, map: &ColumnMap) -> Result<PointsSet, ReadingError> {
+    parse_columns(&read_to_string(filename)?, map)
+}
+
+/// Reads Fourier coefficients written in the file named filename, in the
+/// given format, with their header.
+/// RETURN           Result<(CoeffsSet, CoeffsHeader), ReadingError>
+#[allow(dead_code)]
+pub fn read_fourier_coeffs (filename: &str, format: FileFormat)
+    -> Result<(CoeffsSet, CoeffsHeader), ReadingError> {
+    match format {
+        FileFormat::Text => parse_coeffs(&read_to_string(filename)?),
+        FileFormat::Json => coeffs_from_json(&json::parse(&read_to_string(filename)?)?),
+        FileFormat::Svg  => Err(ReadingError::PathError(String::from(
+            "SVG paths hold no Fourier coefficients"))),
+        FileFormat::Columns => Err(ReadingError::ColumnsError(String::from(
+            "columns hold no Fourier coefficients"))),
+    }
 }
 
 
@@ -229,4 +689,186 @@ Parsing floats accepts following formattings:
     'inf', '-inf', 'NaN'
 
     Copied-pasted from https://doc.rust-lang.org/std/str/trait.FromStr.html
-*/
\ No newline at end of file
+*/
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::write;
+
+    /* A sparse set of coefficients, with values that are not short decimals,
+     * whose last harmonic is 0 */
+    fn sparse_coeffs() -> CoeffsSet {
+        let mut set = CoeffsSet::new(7);
+        set.ppos[0] = Complex { re: 0.1, im: -1.0 / 3.0 };
+        set.ppos[2] = Complex { re: std::f64::consts::PI, im: 0.0 };
+        set.nneg[5] = Complex { re: -2.5e-300, im: 1e300 };
+        set
+    }
+
+    fn header() -> CoeffsHeader {
+        CoeffsHeader { period: Some(14.2), origin: Some(-0.7), terms: None,
+                       source: Some(String::from("drawings/heart points.txt")) }
+    }
+
+    fn assert_same_coeffs(got: &(CoeffsSet, CoeffsHeader), want: &(CoeffsSet, CoeffsHeader)) {
+        let pairs = |set: &CoeffsSet| set.ppos.iter().chain(set.nneg.iter().skip(1))
+                                              .map(|c| (c.re, c.im)).collect::<Vec<_>>();
+        assert_eq!(got.0.ppos.len(), want.0.ppos.len());
+        assert_eq!(pairs(&got.0), pairs(&want.0));
+        assert_eq!((got.1.period, got.1.origin), (want.1.period, want.1.origin));
+        assert_eq!(got.1.source, want.1.source);
+    }
+
+    fn points() -> PointsSet {
+        PointsSet { xx: vec![40.0, 0.1, -1.0 / 3.0, 2e-7], yy: vec![0.0, 40.0, 1e10, -5.5],
+                    tt: vec![0.0, 1.5, 2.0, 3.25], mm: vec![false, true, false, true],
+                    boundary: Some(Boundary::Clamped(vec![1.0, 0.5], vec![-2.0, 0.0])) }
+    }
+
+    fn assert_same_points(got: &PointsSet, want: &PointsSet) {
+        assert_eq!((&got.tt, &got.xx, &got.yy), (&want.tt, &want.xx, &want.yy));
+        assert_eq!(got.mm, want.mm);
+        assert_eq!(got.boundary, want.boundary);
+    }
+
+    #[test]
+    fn written_coeffs_are_read_back() {
+        let coeffs = (sparse_coeffs(), header());
+        let text = write::coeffs_to_string(&coeffs.0, &coeffs.1);
+        let read = parse_coeffs(&text).ok().unwrap();
+        assert_same_coeffs(&read, &coeffs);
+        // the zero harmonics being left out, but the last one
+        assert_eq!(read.1.terms, Some(4));
+
+        let doc = json::parse(&write::coeffs_to_json(&coeffs.0, &coeffs.1).to_string()).unwrap();
+        assert_same_coeffs(&coeffs_from_json(&doc).ok().unwrap(), &coeffs);
+    }
+
+    #[test]
+    fn coeffs_go_from_text_to_json_and_back() {
+        let text = write::coeffs_to_string(&sparse_coeffs(), &header());
+        let (set, header) = parse_coeffs(&text).ok().unwrap();
+        let doc = json::parse(&write::coeffs_to_json(&set, &header).to_pretty_string()).unwrap();
+        let (set, header) = coeffs_from_json(&doc).ok().unwrap();
+        assert_eq!(write::coeffs_to_string(&set, &header), text);
+    }
+
+    #[test]
+    fn points_go_from_text_to_json_and_back() {
+        let text = write::points_to_string(&points());
+        let set = text.parse::<PointsSet>().ok().unwrap();
+        assert_same_points(&set, &points());
+        let doc = json::parse(&write::points_to_json(&set).to_string()).unwrap();
+        let set = points_from_json(&doc).ok().unwrap();
+        assert_same_points(&set, &points());
+        assert_eq!(write::points_to_string(&set), text);
+    }
+
+    fn columns(s: &str, map: &str) -> Result<PointsSet, ReadingError> {
+        parse_columns(s, &map.parse::<ColumnMap>().ok().unwrap())
+    }
+
+    #[test]
+    fn columns_are_split_by_commas_if_any() {
+        let set = columns("0 1 2\n1\t3 \t 4\n", "1,2,3").ok().unwrap();
+        assert_eq!((set.tt, set.xx, set.yy), (vec![0.0, 1.0], vec![1.0, 3.0], vec![2.0, 4.0]));
+        let set = columns("0, 1 ,2\n1,3,  4", "1,2,3").ok().unwrap();
+        assert_eq!((set.tt, set.xx, set.yy), (vec![0.0, 1.0], vec![1.0, 3.0], vec![2.0, 4.0]));
+        // a single comma makes the other lines one field
+        assert!(matches!(columns("0,1,2\n1 3 4", "1,2,3"), Err(ReadingError::ColumnsError(_))));
+    }
+
+    #[test]
+    fn columns_may_be_named_by_a_header_row() {
+        let s = "x,time,y,other\n1,0,2,a\n3,1,4,b\n";
+        let set = columns(s, "time,x,y").ok().unwrap();
+        assert_eq!((set.tt, set.xx, set.yy), (vec![0.0, 1.0], vec![1.0, 3.0], vec![2.0, 4.0]));
+        let set = columns(s, "2,1,y").ok().unwrap();
+        assert_eq!(set.xx, vec![1.0, 3.0]);
+        assert!(matches!(columns(s, "t,x,y"), Err(ReadingError::ColumnsError(_))));
+        // a row with a number is not a header
+        assert!(matches!(columns("t,1,y\n0,1,2\n1,3,4", "t,x,y"),
+                         Err(ReadingError::ColumnsError(_))));
+        assert!(matches!(columns("t 1 y\n0 1 2\n1 3 4", "1,2,3"),
+                         Err(ReadingError::ColumnsError(_))));
+        // nor is a header left with a single row
+        assert!(matches!(columns("t x y\n0 1 2", "t,x,y"), Err(ReadingError::NotEnoughPoints)));
+
+        assert!(matches!("0,1,2".parse::<ColumnMap>(), Err(ReadingError::ColumnsError(_))));
+        assert!(matches!("1,2".parse::<ColumnMap>(), Err(ReadingError::ColumnsError(_))));
+    }
+
+    #[test]
+    fn comments_of_columns_are_skipped() {
+        let s = "% from Octave\n# boundary: not-a-knot\n\n  # t x y\n0 1 2\n%1 2 3\n1 3 4\n";
+        let set = columns(s, "1,2,3").ok().unwrap();
+        assert_eq!(set.tt, vec![0.0, 1.0]);
+        assert_eq!(set.boundary, Some(Boundary::NotAKnot));
+    }
+
+    #[test]
+    fn mode_column_sets_the_interpolation() {
+        let s = "0 1 2 l\n1 3 4 line\n2 5 6 1\n3 7 8 0.5\n4 9 0 0\n5 1 2 s\n6 3 4 spline\n";
+        let set = columns(s, "1,2,3,4").ok().unwrap();
+        assert_eq!(set.mm, vec![true, true, true, true, false, false, false]);
+        assert!(columns(s, "1,2,3").ok().unwrap().mm.iter().all(|&m| !m));
+        assert!(matches!(columns("0 1 2 x\n1 3 4 l", "1,2,3,4"),
+                         Err(ReadingError::ColumnsError(_))));
+        assert!(matches!(columns("0 1 2\n1 3 4 l", "1,2,3,4"),
+                         Err(ReadingError::ColumnsError(_))));
+
+        // the column named `mode` is used without being given
+        let s = "t,x,y,mode\n0,1,2,line\n1,3,4,\n";
+        assert_eq!(columns(s, "t,x,y").ok().unwrap().mm, vec![true, false]);
+    }
+
+    #[test]
+    fn complex_needs_two_parts() {
+        let c = "(1.5, -2)".parse::<Complex>().ok().unwrap();
+        assert_eq!((c.re, c.im), (1.5, -2.0));
+        for s in ["(1.5)", "", "(1, 2, 3)"] {
+            assert!(matches!(s.parse::<Complex>(), Err(ReadingError::IllFormedCoeffs)), "{}", s);
+        }
+        assert!(matches!(parse_coeffs("(1, 2) & (3)"), Err(ReadingError::IllFormedCoeffs)));
+    }
+
+    #[test]
+    fn harmonics_are_bounded() {
+        let (set, _) = parse_coeffs("3: (1, 2) & (3, 4)\n(5, 6) & (7, 8)").ok().unwrap();
+        assert_eq!(set.ppos.len(), 5);
+        assert_eq!((set.nneg[4].re, set.nneg[4].im), (7.0, 8.0));
+
+        let last = format!("{}: (1, 2) & (3, 4)", MAX_HARMONIC);
+        assert!(parse_coeffs(&last).is_ok());
+        for s in [format!("{}: (1, 2) & (3, 4)", MAX_HARMONIC + 1),
+                  format!("{}\n(0, 0) & (0, 0)", last),
+                  format!("{}: (1, 2) & (3, 4)\n(0, 0) & (0, 0)", usize::MAX),
+                  String::from("99999999999999999999999: (1, 2) & (3, 4)")] {
+            assert!(matches!(parse_coeffs(&s), Err(ReadingError::IllFormedCoeffs)), "{}", s);
+        }
+
+        let doc = json::parse(r#"{"terms": [{"k": -1e300, "re": 1, "im": 0}]}"#).unwrap();
+        assert!(matches!(coeffs_from_json(&doc), Err(ReadingError::JsonError(_))));
+    }
+
+    #[test]
+    fn clamped_needs_both_slopes() {
+        assert_eq!("clamped (1, -2) (0.5, 3)".parse::<Boundary>().ok(),
+                   Some(Boundary::Clamped(vec![1.0, -2.0], vec![0.5, 3.0])));
+        for s in ["clamped", "clamped ", "clamped (1, 2)", "clamped (1, 2) (3, 4) (5, 6)",
+                  "clamped (1, 2) (3)"] {
+            assert!(matches!(s.parse::<Boundary>(), Err(ReadingError::BoundaryError(_))), "{}", s);
+        }
+    }
+
+    #[test]
+    fn missing_colon_is_pointed_after_the_time() {
+        let column = |s: &str| s.parse::<Point>().err().map(|e| (e.column, e.message));
+        assert_eq!(column("1 (3, 4)"), Some((2, "expected `:` after the time")));
+        assert_eq!(column("  12.5 l (3, 4)"), Some((7, "expected `:` after the time")));
+        assert_eq!(column("1(3, 4)"), Some((2, "expected `:` after the time")));
+        assert_eq!(column("1"), Some((2, "expected `:` after the time")));
+        assert_eq!(column("1: (3, 4"), Some((9, "expected `)` after the coordinates")));
+        assert!(column("1: l (3, 4)").is_none());
+    }
+}
@@ -0,0 +1,465 @@
+use std::cell::Cell;
+use std::fs::File;
+use std::io::{self, BufWriter, Error, ErrorKind, Seek, SeekFrom, Write};
+use std::path::Path;
+use std::rc::Rc;
+
+use super::color::Rgb;
+use super::fgif::{DrawOptions, MyGif, Palette};
+use super::png;
+use super::term::Preview;
+
+/// Destination of the frames produced by the drawing functions. Frames are
+/// given row after row, as palette indices, or as colours to the true colour
+/// outputs.
+pub trait FrameSink {
+    /// Called once, before any frame, with the size of the frames and the
+    /// palette they are drawn with.
+    fn start(&mut self, width: usize, height: usize, palette: &Palette) -> Result<(), Error>;
+    /// Writes the frame t of palette indices, that stays for n frame durations.
+    fn write_frame(&mut self, t: &[u8], n: usize) -> Result<(), Error>;
+    /// Tells whether frames are better given as colours, to `write_rgb_frame`.
+    fn true_color(&self) -> bool {
+        false
+    }
+    /// Writes the frame t of colours, as `write_frame` does, for the outputs
+    /// that are not limited to a palette.
+    fn write_rgb_frame(&mut self, _t: &[Rgb], _n: usize) -> Result<(), Error> {
+        Err(Error::new(ErrorKind::InvalidInput, "this output only takes palette indices"))
+    }
+    /// Ends the output. Returns the number of bytes written, and the number of
+    /// bytes that would have been written without delta frames if they were
+    /// used.
+    fn finish(&mut self) -> Result<(usize, Option<usize>), Error>;
+}
+
+/// Output formats of the animations.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Format {
+    /// Animated GIF
+    Gif,
+    /// One numbered PNG file per frame
+    PngSequence,
+    /// Animated PNG
+    Apng,
+    /// Animated SVG, which is not made of frames: cf `svg`
+    Svg,
+    /// Still PDF drawing, cf `pdf`
+    Pdf,
+    /// Uncompressed YUV4MPEG2 video
+    Y4m,
+    /// Animation played in the terminal, cf `term`
+    Terminal,
+}
+
+impl Format {
+    /// Returns the format given by the extension of path: `.png` files are
+    /// sequences, `.apng` animated PNG, `.svg` SVG, `.pdf` PDF, `.y4m` Y4M,
+    /// and anything else GIF. `-`, the standard output, is Y4M as well.
+    pub fn from_path(path: &str) -> Format {
+        if path == STDOUT {   return Format::Y4m   }
+        let ext = Path::new(path).extension()
+                                 .and_then(|e| e.to_str())
+                                 .map(|e| e.to_lowercase());
+        match ext.as_deref() {
+            Some("png")  => Format::PngSequence,
+            Some("apng") => Format::Apng,
+            Some("svg")  => Format::Svg,
+            Some("pdf")  => Format::Pdf,
+            Some("y4m")  => Format::Y4m,
+            _            => Format::Gif,
+        }
+    }
+
+    /// Whether frames are written in colours rather than palette indices, so
+    /// that anti-aliasing blends colours instead of choosing among shades.
+    pub fn true_color(self) -> bool {
+        matches!(self, Format::PngSequence | Format::Apng | Format::Y4m)
+    }
+}
+
+/// Output path standing for the standard output.
+pub const STDOUT: &str = "-";
+
+/// Creates the output path, in the given format, with the timing of opts. The
+/// path is ignored by the terminal, that always plays on the standard output.
+/// Fails for formats that are not made of frames, and for the standard output
+/// if the format is not Y4M.
+pub fn create(path: &str, format: Format, opts: &DrawOptions)
+    -> Result<Box<dyn FrameSink>, Error> {
+    if path == STDOUT && format != Format::Y4m {
+        return Err(Error::new(ErrorKind::InvalidInput,
+            "only Y4M video can be written to the standard output"))
+    }
+    Ok(match format {
+        Format::Gif => Box::new(MyGif::new(File::create(path)?, opts.fps, opts.loops,
+            opts.delta, opts.compare)),
+        Format::PngSequence => Box::new(PngSequence::new(path)),
+        Format::Apng => Box::new(Apng::new(File::create(path)?, opts.fps, opts.loops,
+            opts.delta, opts.compare)),
+        Format::Y4m => {
+            let out: Box<dyn Write> = if path == STDOUT { Box::new(io::stdout()) }
+                                      else              { Box::new(File::create(path)?) };
+            Box::new(Y4m::new(out, opts.fps))
+        }
+        Format::Terminal => Box::new(Preview::new(io::stdout(), opts.fps)),
+        Format::Svg | Format::Pdf => return Err(Error::new(ErrorKind::InvalidInput,
+            "SVG and PDF outputs are not made of frames")),
+    })
+}
+
+/// Writer that counts the bytes going through it.
+pub struct Counter<W: Write> {
+    pub inner: W,
+    pub count: Rc<Cell<usize>>,
+}
+
+impl<W: Write> Write for Counter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        self.count.set(self.count.get() + n);
+        Ok(n)
+    }
+    fn flush(&mut self) -> io::Result<()> {   self.inner.flush()   }
+}
+
+/// Returns the delay of a frame staying n frame durations, after elapsed
+/// ones, in units of 1/unit second. Delays are rounded so that the rounding
+/// errors do not add up over the animation.
+pub fn delay_for(elapsed: usize, n: usize, fps: f64, unit: f64) -> usize {
+    let units = |frames: usize| (frames as f64 * unit / fps).round() as usize;
+    units(elapsed + n) - units(elapsed)
+}
+
+/// Returns the smallest rectangle (xmin, xmax, ymin, ymax), bounds included,
+/// containing all the pixels that differ between the frames a and b of width
+/// w. `None` if they are the same.
+pub fn changed_rect<T: PartialEq>(a: &[T], b: &[T], w: usize)
+    -> Option<(usize, usize, usize, usize)> {
+    let (mut xmin, mut xmax, mut ymin, mut ymax) = (usize::MAX, 0, usize::MAX, 0);
+    for (i, (old, new)) in a.iter().zip(b.iter()).enumerate() {
+        if old != new {
+            let (x, y) = (i % w, i / w);
+            xmin = xmin.min(x);     xmax = xmax.max(x);
+            ymin = ymin.min(y);     ymax = ymax.max(y);
+        }
+    }
+    if xmin > xmax { None } else { Some((xmin, xmax, ymin, ymax)) }
+}
+
+/* Returns the colours of the palette indices of the frame t, for the true
+ * colour outputs that are given one. */
+fn to_colors(t: &[u8], palette: &[Rgb]) -> Vec<Rgb> {
+    t.iter().map(|&idx| palette[idx as usize]).collect()
+}
+
+/* Returns the colours of palette, indexed as the frames index them */
+fn palette_colors(palette: &Palette) -> Vec<Rgb> {
+    palette.to_bytes().chunks(3).map(|c| (c[0], c[1], c[2])).collect()
+}
+
+/* Returns the 24 bits RGB image of the rectangle (x, y, w, h) of the frame t,
+ * whose width is tabw */
+fn to_rgb(t: &[Rgb], tabw: usize, rect: (usize, usize, usize, usize)) -> Vec<u8> {
+    let (x0, y0, w, h) = rect;
+    let mut rgb = Vec::with_capacity(3 * w * h);
+    for y in y0..y0 + h {
+        for c in &t[y*tabw + x0..y*tabw + x0 + w] {
+            rgb.extend_from_slice(&[c.0, c.1, c.2]);
+        }
+    }
+    rgb
+}
+
+/// Writes each frame in its own 24 bits PNG file, numbered after the output
+/// path: `out.png` gives `out_0000.png`, `out_0001.png`, ... Frames that stay
+/// for several frame durations are repeated, so that the sequence has a
+/// constant frame rate.
+/// Frames are drawn in colours, cf `fgif::RgbFrame`.
+pub struct PngSequence {
+    /// Path without its extension
+    stem: String,
+    width: usize,
+    height: usize,
+    /// Colours of the palette indices, for the frames given as such
+    colors: Vec<Rgb>,
+    written: usize,
+    bytes: usize,
+}
+
+impl PngSequence {
+    pub fn new(path: &str) -> PngSequence {
+        let stem = match path.rfind('.') {
+            Some(i) if !path[i..].contains('/') => &path[..i],
+            _ => path,
+        };
+        PngSequence { stem: String::from(stem), width: 0, height: 0, colors: Vec::new(),
+                      written: 0, bytes: 0 }
+    }
+}
+
+impl FrameSink for PngSequence {
+    fn start(&mut self, width: usize, height: usize, palette: &Palette) -> Result<(), Error> {
+        self.width = width;
+        self.height = height;
+        self.colors = palette_colors(palette);
+        Ok(())
+    }
+
+    fn write_frame(&mut self, t: &[u8], n: usize) -> Result<(), Error> {
+        self.write_rgb_frame(&to_colors(t, &self.colors), n)
+    }
+
+    fn true_color(&self) -> bool {
+        true
+    }
+
+    fn write_rgb_frame(&mut self, t: &[Rgb], n: usize) -> Result<(), Error> {
+        let (w, h) = (self.width, self.height);
+        let data = png::image_data(&to_rgb(t, w, (0, 0, w, h)), w, h);
+
+        for _ in 0..n {
+            let name = format!("{}_{:04}.png", self.stem, self.written);
+            let mut f = BufWriter::new(File::create(name)?);
+            f.write_all(&png::SIGNATURE)?;
+            self.bytes += png::SIGNATURE.len();
+            self.bytes += png::write_chunk(&mut f, b"IHDR", &png::ihdr(w, h))?;
+            self.bytes += png::write_chunk(&mut f, b"IDAT", &data)?;
+            self.bytes += png::write_chunk(&mut f, b"IEND", &[])?;
+            self.written += 1;
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<(usize, Option<usize>), Error> {
+        Ok((self.bytes, None))
+    }
+}
+
+/// Writes the frames as an animated PNG of 24 bits colours, drawn as for
+/// `PngSequence`.
+/// see https://wiki.mozilla.org/APNG_Specification
+pub struct Apng {
+    file: BufWriter<File>,
+    width: usize,
+    height: usize,
+    /// Colours of the palette indices, for the frames given as such
+    colors: Vec<Rgb>,
+    fps: f64,
+    loops: usize,
+    /// Wether frames are written as the rectangle that changed
+    delta: bool,
+    /// Wether the full frames are compressed too, to compare the sizes
+    compare: bool,
+    /// Number of frames written, needed in the acTL chunk
+    frames: usize,
+    /// Number of frame durations elapsed since the start
+    elapsed: usize,
+    /// Sequence number of the next fcTL or fdAT chunk
+    sequence: u32,
+    previous: Option<Vec<Rgb>>,
+    bytes: usize,
+    /// Bytes the image data would have taken without delta frames
+    full_data: usize,
+    delta_data: usize,
+}
+
+/// Position of the acTL chunk, right after the signature and IHDR: it is
+/// rewritten at the end, once the number of frames is known.
+const ACTL_POSITION: u64 = 8 + 25;
+
+impl Apng {
+    /// Creates the animated PNG, played at fps frames per second, loops times
+    /// (0 meaning forever). If delta is set, frames are written as the
+    /// rectangle that changed since the previous one, and also compressed
+    /// whole to know what it saved if compare is set.
+    pub fn new(file: File, fps: f64, loops: usize, delta: bool, compare: bool) -> Apng {
+        Apng { file: BufWriter::new(file), width: 0, height: 0, colors: Vec::new(),
+               fps, loops, delta, compare, frames: 0, elapsed: 0, sequence: 0,
+               previous: None, bytes: 0, full_data: 0, delta_data: 0 }
+    }
+
+    fn actl(&self) -> Vec<u8> {
+        let mut data = (self.frames as u32).to_be_bytes().to_vec();
+        data.extend_from_slice(&(self.loops as u32).to_be_bytes());
+        data
+    }
+
+    /* Writes the fcTL chunk of a frame of rectangle (x, y, w, h) staying
+     * delay_ms milliseconds */
+    fn write_fctl(&mut self, rect: (usize, usize, usize, usize), delay_ms: usize)
+        -> Result<(), Error> {
+        let mut data = self.sequence.to_be_bytes().to_vec();
+        for v in [rect.2, rect.3, rect.0, rect.1].iter() {
+            data.extend_from_slice(&(*v as u32).to_be_bytes());
+        }
+        data.extend_from_slice(&(delay_ms.min(u16::MAX as usize) as u16).to_be_bytes());
+        data.extend_from_slice(&1000u16.to_be_bytes());
+        // dispose: none (keep the frame), blend: source (replace the pixels)
+        data.extend_from_slice(&[0, 0]);
+        self.sequence += 1;
+        self.bytes += png::write_chunk(&mut self.file, b"fcTL", &data)?;
+        Ok(())
+    }
+}
+
+impl FrameSink for Apng {
+    fn start(&mut self, width: usize, height: usize, palette: &Palette) -> Result<(), Error> {
+        self.width = width;
+        self.height = height;
+        self.colors = palette_colors(palette);
+
+        self.file.write_all(&png::SIGNATURE)?;
+        self.bytes += png::SIGNATURE.len();
+        self.bytes += png::write_chunk(&mut self.file, b"IHDR", &png::ihdr(width, height))?;
+        // rewritten by finish, with the right number of frames
+        let actl = self.actl();
+        self.bytes += png::write_chunk(&mut self.file, b"acTL", &actl)?;
+        Ok(())
+    }
+
+    fn write_frame(&mut self, t: &[u8], n: usize) -> Result<(), Error> {
+        self.write_rgb_frame(&to_colors(t, &self.colors), n)
+    }
+
+    fn true_color(&self) -> bool {
+        true
+    }
+
+    fn write_rgb_frame(&mut self, t: &[Rgb], n: usize) -> Result<(), Error> {
+        let (w, h) = (self.width, self.height);
+        let delay = delay_for(self.elapsed, n, self.fps, 1000.0);
+        self.elapsed += n;
+
+        let whole = (0, 0, w, h);
+        let rect = match self.previous.as_ref() {
+            None => whole,
+            Some(previous) => match changed_rect(previous, t, w) {
+                Some((xmin, xmax, ymin, ymax)) => (xmin, ymin, xmax - xmin + 1, ymax - ymin + 1),
+                None => (0, 0, 1, 1),
+            },
+        };
+        let data = png::image_data(&to_rgb(t, w, rect), rect.2, rect.3);
+        if self.delta && self.compare {
+            self.full_data += if rect == whole { data.len() }
+                              else { png::image_data(&to_rgb(t, w, whole), w, h).len() };
+            self.delta_data += data.len();
+        }
+
+        self.write_fctl(rect, delay)?;
+        if self.frames == 0 {
+            self.bytes += png::write_chunk(&mut self.file, b"IDAT", &data)?;
+        } else {
+            let mut fdat = self.sequence.to_be_bytes().to_vec();
+            fdat.extend_from_slice(&data);
+            self.sequence += 1;
+            self.bytes += png::write_chunk(&mut self.file, b"fdAT", &fdat)?;
+        }
+        self.frames += 1;
+        if self.delta {
+            self.previous = Some(t.to_vec());
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<(usize, Option<usize>), Error> {
+        self.bytes += png::write_chunk(&mut self.file, b"IEND", &[])?;
+        let actl = self.actl();
+        self.file.seek(SeekFrom::Start(ACTL_POSITION))?;
+        png::write_chunk(&mut self.file, b"acTL", &actl)?;
+        self.file.flush()?;
+
+        let full = if self.delta && self.compare {
+            Some(self.bytes + self.full_data - self.delta_data)
+        } else {
+            None
+        };
+        Ok((self.bytes, full))
+    }
+}
+
+/// Writes the frames as an uncompressed YUV4MPEG2 video, with full resolution
+/// chroma, that video encoders read. Frames that stay for several frame
+/// durations are repeated, so that the video has a constant frame rate.
+/// see https://wiki.multimedia.cx/index.php/YUV4MPEG2
+pub struct Y4m {
+    out: BufWriter<Box<dyn Write>>,
+    width: usize,
+    height: usize,
+    fps: f64,
+    /// Colours of the palette indices, for the frames given as such
+    colors: Vec<Rgb>,
+    bytes: usize,
+}
+
+impl Y4m {
+    /// Creates the video, played at fps frames per second, written in out.
+    pub fn new(out: Box<dyn Write>, fps: f64) -> Y4m {
+        Y4m { out: BufWriter::new(out), width: 0, height: 0, fps, colors: Vec::new(),
+              bytes: 0 }
+    }
+
+    /// Returns the frame rate fps as the fraction expected in the header,
+    /// exact to the thousandth, that is 0 for rates under half of it.
+    pub fn rate(fps: f64) -> (u64, u64) {
+        let num = (fps * 1000.0).round() as u64;
+        let gcd = |mut a: u64, mut b: u64| { while b != 0 { let r = a % b; a = b; b = r; } a };
+        let g = gcd(num, 1000).max(1);
+        (num / g, 1000 / g)
+    }
+}
+
+/* Returns the Y, U, V components of c, following BT.601 with the limited range
+ * that most encoders assume. */
+fn to_yuv(c: Rgb) -> (u8, u8, u8) {
+    let (r, g, b) = (c.0 as f64, c.1 as f64, c.2 as f64);
+    let y = 16.0 + ( 65.481 * r + 128.553 * g +  24.966 * b) / 255.0;
+    let u = 128.0 + (-37.797 * r -  74.203 * g + 112.0   * b) / 255.0;
+    let v = 128.0 + (112.0   * r -  93.786 * g -  18.214 * b) / 255.0;
+    (y.round() as u8, u.round() as u8, v.round() as u8)
+}
+
+impl FrameSink for Y4m {
+    fn start(&mut self, width: usize, height: usize, palette: &Palette) -> Result<(), Error> {
+        self.width = width;
+        self.height = height;
+        self.colors = palette_colors(palette);
+
+        let (num, den) = Y4m::rate(self.fps);
+        let header = format!("YUV4MPEG2 W{} H{} F{}:{} Ip A1:1 C444\n", width, height,
+            num, den);
+        self.out.write_all(header.as_bytes())?;
+        self.bytes += header.len();
+        Ok(())
+    }
+
+    fn write_frame(&mut self, t: &[u8], n: usize) -> Result<(), Error> {
+        self.write_rgb_frame(&to_colors(t, &self.colors), n)
+    }
+
+    fn true_color(&self) -> bool {
+        true
+    }
+
+    fn write_rgb_frame(&mut self, t: &[Rgb], n: usize) -> Result<(), Error> {
+        let size = self.width * self.height;
+        let yuv: Vec<(u8, u8, u8)> = t.iter().map(|&c| to_yuv(c)).collect();
+        // the three planes, one after the other
+        let mut frame = Vec::with_capacity(6 + 3 * size);
+        frame.extend_from_slice(b"FRAME\n");
+        frame.extend(yuv.iter().map(|c| c.0));
+        frame.extend(yuv.iter().map(|c| c.1));
+        frame.extend(yuv.iter().map(|c| c.2));
+
+        for _ in 0..n {
+            self.out.write_all(&frame)?;
+            self.bytes += frame.len();
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<(usize, Option<usize>), Error> {
+        self.out.flush()?;
+        Ok((self.bytes, None))
+    }
+}
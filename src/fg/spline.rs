@@ -1,6 +1,4 @@
-extern crate nalgebra as na;
-
-use na::{DMatrix, DVector};
+use std::cmp::Ordering;
 use std::fmt;
 
 /// Represents a cubic spline.
@@ -21,7 +19,9 @@ pub struct Spline {
 }
 
 /// Represents a spline part, i.e. a cubic polynomial. 
-/// Thus, it holds its coefficients a, b, c, d
+/// Thus, it holds its coefficients a, b, c, d, of the powers of u = t - t_i,
+/// t_i being the time at which the part starts, so that they stay well scaled
+/// whatever the times are.
 #[allow(dead_code)]
 #[derive(Copy, Clone, Debug)]
 pub struct SplinePart {
@@ -31,186 +31,440 @@ pub struct SplinePart {
     pub d: f64,
 }
 
-/// Returns the matrix that will have to be solved when
-/// computing a spline interpolating points whose timestamps
-/// are in tt vector 
-fn matrix_for (tt: &Vec<f64>, mm: &Vec<bool>) -> DMatrix<f64> {
-    let n = tt.len();
-    let mut a: DMatrix<f64> = DMatrix::zeros(4*(n-1), 4*(n-1));
+/// Represents what could prevent interpolating points, the points being
+/// given by their index.
+#[derive(Debug)]
+pub enum SplineError {
+    /// Fewer than two points, how many there are
+    NotEnoughPoints(usize),
+    /// Two consecutive points reached at the same time
+    DuplicateTime { first: usize, second: usize, t: f64 },
+    /// A point reached before, or not after, the previous one
+    NonMonotonicTime { previous: usize, point: usize, t_previous: f64, t: f64 },
+    /// The system of the points from first to last has no single solution
+    SingularSystem { first: usize, last: usize },
+    /// The condition of boundary at the start cannot hold, as the last part,
+    /// from the point of index last, is linear, cf `system_for`
+    LinearEnd { boundary: Boundary, last: usize },
+}
 
-    let mut t1_squared: f64 = tt[0] * tt[0];
-    let mut t1_cubed:   f64 = t1_squared * tt[0];
-    let mut t2_squared: f64 = tt[1] * tt[1];
-    let mut t2_cubed:   f64 = t2_squared * tt[1];
+impl fmt::Display for SplineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SplineError::NotEnoughPoints(n) =>
+                { write!(f, "{} point(s), at least 2 are needed", n)  }
+            SplineError::DuplicateTime { first, second, t } =>
+                { write!(f, "the points of index {} and {} are both reached at t = {}",
+                         first, second, t)                             }
+            SplineError::NonMonotonicTime { previous, point, t_previous, t } =>
+                { write!(f, "the point of index {} is reached at t = {}, not after the \
+                         one of index {} at t = {}", point, t, previous, t_previous) }
+            SplineError::SingularSystem { first, last } =>
+                { write!(f, "the spline through the points of index {} to {} cannot be \
+                         computed", first, last)                       }
+            SplineError::LinearEnd { boundary, last } =>
+                { write!(f, "the `{}` boundary does not hold at the start, as the drawing \
+                         ends with a line from the point of index {}", boundary, last) }
+        }
+    }
+}
 
-    for i in 0..(n-1) {
-        if i >= 1 {
-            t1_squared = t2_squared;
-            t1_cubed = t2_cubed;
+/// Checks that there are at least two times in tt, and that they increase,
+/// as interpolating needs.
+pub fn check_times(tt: &[f64]) -> Result<(), SplineError> {
+    if tt.len() < 2 {
+        return Err(SplineError::NotEnoughPoints(tt.len()))
+    }
+    for i in 0..(tt.len()-1) {
+        if tt[i+1] == tt[i] {
+            return Err(SplineError::DuplicateTime { first: i, second: i+1, t: tt[i] })
+        }
+        // also if one of them is NaN
+        if tt[i+1].partial_cmp(&tt[i]) != Some(Ordering::Greater) {
+            return Err(SplineError::NonMonotonicTime { previous: i, point: i+1,
+                                                      t_previous: tt[i], t: tt[i+1] })
         }
-        t2_squared = tt[i+1] * tt[i+1];
-        t2_cubed = t2_squared * tt[i+1];
+    }
+    Ok(())
+}
 
-        a[(4*i, 4*i)]   = t1_cubed;
-        a[(4*i, 4*i+1)] = t1_squared;
-        a[(4*i, 4*i+2)] = tt[i];
-        a[(4*i, 4*i+3)] = 1.0;
+/// Conditions that close the system of a spline at its ends. Only the
+/// periodic and quadratic ones allow the last part to be linear, unless the
+/// start condition holds anyway, cf `start_holds`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Boundary {
+    /// s² a + s b + c = 0 at the start of the first part and at the end of the
+    /// last, or a = b = 0 for the latter if it is linear, where a, b, c are the
+    /// coefficients of the powers of s = t - t_0, t_0 being the time of the
+    /// first point: the drawing starts with a null slope
+    Quadratic,
+    /// The last part ends with the slope and concavity the first one starts
+    /// with, the path being closed first if it is not, cf `interpolate_coords`
+    Periodic,
+    /// The concavity is 0 at both ends
+    Natural,
+    /// The slopes at the start and at the end are given, for each coordinate
+    Clamped(Vec<f64>, Vec<f64>),
+    /// The first two parts are the same polynomial, and so are the last two,
+    /// as Octave's `spline` does. Two points are joined by a line, and three
+    /// by a parabola.
+    NotAKnot,
+}
 
-        a[(4*i+1, 4*i)]   = t2_cubed;
-        a[(4*i+1, 4*i+1)] = t2_squared;
-        a[(4*i+1, 4*i+2)] = tt[i+1];
-        a[(4*i+1, 4*i+3)] = 1.0;
-    
-        if i < n-2 {
-            add_cont_equations(&mut a, tt[i+1], i, mm[i])
+/// Writes the boundary as `read` parses it: `natural`, `not-a-knot`, ... and
+/// `clamped (x, y) (x, y)` with the slopes at the start and at the end.
+impl fmt::Display for Boundary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let slopes = |s: &[f64]| s.iter().map(|v| v.to_string())
+                                   .collect::<Vec<String>>().join(", ");
+        match self {
+            Boundary::Quadratic => write!(f, "quadratic"),
+            Boundary::Periodic  => write!(f, "periodic"),
+            Boundary::Natural   => write!(f, "natural"),
+            Boundary::NotAKnot  => write!(f, "not-a-knot"),
+            Boundary::Clamped(start, end) =>
+                write!(f, "clamped ({}) ({})", slopes(start), slopes(end)),
         }
     }
+}
 
-    /* Starting & ending slopes are 0 : two missing eqations */
-    if mm[n-2] {
-        a[(4*n-6, 4*n-7)] = 1.0;
-        a[(4*n-5, 4*n-8)] = 1.0;
-    } else {
-        a[(4*n-6, 0)] = tt[0] * tt[0];
-        a[(4*n-6, 1)] = tt[0];
-        a[(4*n-6, 2)] = 1.0;
-
-        a[(4*n-5, 4*n-8)] = tt[n-1] * tt[n-1];
-        a[(4*n-5, 4*n-7)] = tt[n-1];
-        a[(4*n-5, 4*n-6)] = 1.0; 
+/// Tells whether the start condition of boundary holds without its equation,
+/// which `system_for` leaves out when the last part is linear: a natural start
+/// on a line, or a not-a-knot one on lines. The quadratic start is left out
+/// silently, as it always was.
+/// mm tells which of the m parts are linear.
+fn start_holds(mm: &[bool], boundary: &Boundary) -> bool {
+    let m = mm.len();
+    match boundary {
+        Boundary::Natural  => mm[0],
+        Boundary::NotAKnot => mm[0] && (m <= 2 || mm[1]),
+        Boundary::Clamped(..) => false,
+        Boundary::Quadratic | Boundary::Periodic => true,
     }
-    
-    a
 }
 
-fn add_cont_equations(a: &mut DMatrix<f64>, t: f64, i: usize, is_linear: bool){
-    // linear interpolation
-    if is_linear {
-        /* So that we justa have a cubic polynomial with a = 0, b = 0 */
-        a[(4*i+2, 4*i)]   = 1.0;
-        a[(4*i+3, 4*i+1)] = 1.0;
+/// Right hand side of a row of the system of a spline, cf `system_for`.
+#[derive(Copy, Clone)]
+enum Value {
+    Zero,
+    /// The coordinate of the point of that index
+    Point(usize),
+    /// The slopes of `Boundary::Clamped`
+    StartSlope,
+    EndSlope,
+}
+
+/// Square band matrix, whose rows only hold non zero elements from `lower`
+/// columns before the main diagonal to `upper` columns after it, plus room
+/// for the `lower` more the row swaps of the elimination may bring.
+struct Banded {
+    n: usize,
+    lower: usize,
+    upper: usize,
+    /// Row r holds the columns from r - lower to r + lower + upper
+    data: Vec<f64>,
+}
+
+impl Banded {
+    fn zeros(n: usize, lower: usize, upper: usize) -> Banded {
+        Banded { n, lower, upper, data: vec![0.0; n * (2 * lower + upper + 1)] }
     }
-    // cubic spline interpolation
-    else {
-        let t_squared = t * t;
-        /* Slope continuousity */
-        a[(4*i+2, 4*i)]   = 3.0*t_squared;
-        a[(4*i+2, 4*i+1)] = 2.0*t;
-        a[(4*i+2, 4*i+2)] = 1.0;
 
-        a[(4*i+2, 4*i+4)] = -3.0*t_squared;
-        a[(4*i+2, 4*i+5)] = -2.0*t;
-        a[(4*i+2, 4*i+6)] = -1.0;
-    
-        /* Concavity continousity */
-        a[(4*i+3, 4*i)]   = 6.0*t;
-        a[(4*i+3, 4*i+1)] = 2.0;
+    fn index(&self, r: usize, c: usize) -> usize {
+        r * (2 * self.lower + self.upper + 1) + c + self.lower - r
+    }
 
-        a[(4*i+3, 4*i+4)] = -6.0*t;
-        a[(4*i+3, 4*i+5)] = -2.0;
+    fn get(&self, r: usize, c: usize) -> f64 {
+        self.data[self.index(r, c)]
     }
-}
 
-/// Interpolates points (t[i], x[i]) with a cubic
-/// spline and returns it. 
-/// May fail if some timestamps are equal, thus trying to
-/// inverse a matrix which determinant is 0.
-#[allow(dead_code)]
-pub fn interpolate (xx: &Vec<f64>, tt: &Vec<f64>, mm: &Vec<bool>) -> Spline {
-    assert_eq!(xx.len(), tt.len());
-    let n = xx.len();
-    let mut s = Spline {parts: Vec::new(),
-                        changes: Vec::new(),
-                        current: 0, start: xx[0],
-                        end: xx[xx.len()-1]};
+    fn set(&mut self, r: usize, c: usize, value: f64) {
+        let i = self.index(r, c);
+        self.data[i] = value;
+    }
+
+    /// Solves the system for each right hand side of bb, by gaussian
+    /// elimination with partial pivoting, in O(n) time. Returns the column
+    /// without pivot if the matrix is singular.
+    fn solve(mut self, mut bb: Vec<Vec<f64>>) -> Result<Vec<Vec<f64>>, usize> {
+        let (n, lower, upper) = (self.n, self.lower, self.upper);
+        for k in 0..n {
+            let last_row = (k + lower + 1).min(n);
+            let last_col = (k + lower + upper + 1).min(n);
+            let p = (k..last_row).max_by(|&i, &j| self.get(i, k).abs()
+                                                     .partial_cmp(&self.get(j, k).abs())
+                                                     .unwrap_or(Ordering::Equal))
+                                 .unwrap();
+            if self.get(p, k) == 0.0 {
+                return Err(k)
+            }
+            if p != k {
+                for c in k..last_col {
+                    let (vk, vp) = (self.get(k, c), self.get(p, c));
+                    self.set(k, c, vp);
+                    self.set(p, c, vk);
+                }
+                for b in bb.iter_mut() {
+                    b.swap(k, p);
+                }
+            }
+
+            let pivot = self.get(k, k);
+            for r in (k + 1)..last_row {
+                let f = self.get(r, k) / pivot;
+                if f == 0.0 { continue }
+                for c in (k + 1)..last_col {
+                    let v = self.get(r, c) - f * self.get(k, c);
+                    self.set(r, c, v);
+                }
+                for b in bb.iter_mut() {
+                    b[r] -= f * b[k];
+                }
+            }
+        }
 
-    let a: DMatrix<f64> = matrix_for(&tt, &mm);
-    let mut b: DVector<f64> = DVector::zeros(4*(n-1));
+        for b in bb.iter_mut() {
+            for k in (0..n).rev() {
+                let last_col = (k + lower + upper + 1).min(n);
+                let sum: f64 = ((k + 1)..last_col).map(|c| self.get(k, c) * b[c]).sum();
+                b[k] = (b[k] - sum) / self.get(k, k);
+            }
+        }
+        Ok(bb)
+    }
+}
+
+/// Returns the order in which the m parts of a spline have their unknowns
+/// and equations in its system. Periodic ones are folded, as 0, m-1, 1, m-2,
+/// ... so that the last part is next to the first, and the matrix stays a
+/// band one, with all the parts at most two places away from the next.
+fn parts_order(m: usize, boundary: &Boundary) -> Vec<usize> {
+    match boundary {
+        Boundary::Periodic => (0..m).map(|p| if p % 2 == 0 { p / 2 } else { m - 1 - p / 2 })
+                                    .collect(),
+        _                  => (0..m).collect(),
+    }
+}
 
-    // Building `b` vector:
-    for i in 0..(n-1) {
-        s.changes.push(tt[i]);
+/// Returns the system that will have to be solved when computing a spline
+/// interpolating points whose timestamps are in tt vector, whose unknowns
+/// are the coefficients a, b, c, d of each part, the right hand side of each
+/// row, and the order of the parts.
+/// Each part i takes its values at tt[i] and tt[i+1], i.e. at u = 0 and
+/// u = h = tt[i+1] - tt[i], and at its end the slope and concavity the next
+/// part starts with, or a = b = 0 if it is linear. The
+/// ends are closed by boundary: the last part is followed by the first if it
+/// is periodic. Otherwise, there is an equation at the start, and one at the
+/// end of the last part, unless it is linear, in which case a = b = 0 for it
+/// and there is none at the start, cf `start_holds`.
+/// The parts are ordered by `parts_order`, after the start equation if any,
+/// so that the matrix is a band one.
+fn system_for (tt: &[f64], mm: &[bool], boundary: &Boundary)
+ -> (Banded, Vec<Value>, Vec<usize>) {
+    let n = tt.len();
+    let m = n - 1;
+    let order = parts_order(m, boundary);
+    let mut place = vec![0; m];
+    for (p, &i) in order.iter().enumerate() {
+        place[i] = p;
+    }
+    // first unknown of part i
+    let col = |i: usize| 4 * place[i];
 
-        b[4*i]   = xx[i];
-        b[(4*i+1)] = xx[(i+1)];
+    let mut a = match boundary {
+        Boundary::Periodic => Banded::zeros(4*m, 11, 11),
+        // the end equation reaches the part before the last
+        Boundary::NotAKnot => Banded::zeros(4*m, 7, 4),
+        _                  => Banded::zeros(4*m, 4, 4),
+    };
+    let mut values = Vec::with_capacity(4*m);
+    let mut row = 0;
+    let mut equation = |a: &mut Banded, terms: &[(usize, f64)], value: Value| {
+        // terms add up, a periodic part of its own being its next one
+        for &(c, v) in terms {
+            let sum = a.get(row, c) + v;
+            a.set(row, c, sum);
+        }
+        values.push(value);
+        row += 1;
     };
-    s.changes.push(tt[n-1]);
 
-    // println!("{}{}", a, b);
-    let dec = a.lu();// critical point lol
-    let x = dec.solve(&b).expect("Computation of spline's coefficients failed !");
+    if *boundary != Boundary::Periodic && !mm[n-2] {
+        match boundary {
+            Boundary::Natural    =>
+                equation(&mut a, &[(1, 2.0)], Value::Zero),
+            Boundary::Clamped(..) =>
+                equation(&mut a, &[(2, 1.0)], Value::StartSlope),
+            Boundary::NotAKnot if m > 2 =>
+                equation(&mut a, &[(0, 1.0), (col(1), -1.0)], Value::Zero),
+            Boundary::NotAKnot   =>
+                equation(&mut a, &[(0, 1.0)], Value::Zero),
+            // s² a + s b + c = 0 of the coefficients in s = t - tt[0], at s = 0
+            _                    =>
+                equation(&mut a, &[(2, 1.0)], Value::Zero),
+        }
+    }
+
+    for &i in order.iter() {
+        let h = tt[i+1] - tt[i];
+        let c = col(i);
+        equation(&mut a, &[(c+3, 1.0)], Value::Point(i));
+        equation(&mut a, &[(c, h.powi(3)), (c+1, h * h), (c+2, h), (c+3, 1.0)],
+            Value::Point(i+1));
 
-    // println!("{}", x);
-    for i in 0..(n-1) {
-        s.parts.push(SplinePart{
-            a: x[4*i],
-            b: x[4*i+1],
-            c: x[4*i+2],
-            d: x[4*i+3],
-        });
+        if mm[i] {
+            /* So that we justa have a cubic polynomial with a = 0, b = 0 */
+            equation(&mut a, &[(c, 1.0)], Value::Zero);
+            equation(&mut a, &[(c+1, 1.0)], Value::Zero);
+        } else if i < m-1 || *boundary == Boundary::Periodic {
+            // the first part follows the last one if periodic
+            let next = if i < m-1 { col(i+1) } else { col(0) };
+            /* Slope continuousity */
+            equation(&mut a, &[(c, 3.0 * h * h), (c+1, 2.0 * h), (c+2, 1.0), (next+2, -1.0)],
+                Value::Zero);
+            /* Concavity continousity */
+            equation(&mut a, &[(c, 6.0 * h), (c+1, 2.0), (next+1, -2.0)], Value::Zero);
+        } else {
+            match boundary {
+                Boundary::Natural    =>
+                    equation(&mut a, &[(c, 6.0 * h), (c+1, 2.0)], Value::Zero),
+                Boundary::Clamped(..) =>
+                    equation(&mut a, &[(c, 3.0 * h * h), (c+1, 2.0 * h), (c+2, 1.0)],
+                        Value::EndSlope),
+                Boundary::NotAKnot if m > 2 =>
+                    equation(&mut a, &[(c, 1.0), (col(i-1), -1.0)], Value::Zero),
+                // a parabola, or a line
+                Boundary::NotAKnot if m == 2 =>
+                    equation(&mut a, &[(c, 1.0)], Value::Zero),
+                Boundary::NotAKnot   =>
+                    equation(&mut a, &[(c+1, 1.0)], Value::Zero),
+                // s² a + s b + c = 0 of the coefficients in s = t - tt[0], at
+                // s = tt[i+1] - tt[0], those in u = s - s1 being translated
+                _                    => {
+                    let (t1, t2) = (tt[i] - tt[0], tt[i+1] - tt[0]);
+                    equation(&mut a, &[(c, t2 * t2 - 3.0 * t1 * t2 + 3.0 * t1 * t1),
+                        (c+1, t2 - 2.0 * t1), (c+2, 1.0)], Value::Zero)
+                }
+            }
+        }
     }
-    s
+    (a, values, order)
+}
+
+/// Interpolates points (t[i], x[i]) with a cubic
+/// spline and returns it. 
+/// Fails if the timestamps do not increase, cf `check_times`, or if the
+/// matrix to inverse is singular anyway.
+#[allow(dead_code)]
+pub fn interpolate (xx: &[f64], tt: &[f64], mm: &[bool], boundary: &Boundary)
+ -> Result<Spline, SplineError> {
+    assert_eq!(xx.len(), tt.len());
+    Ok(interpolate_coords(vec![xx.to_vec()], tt, mm, boundary)?.remove(0))
 }
 
 /// Iterpolates sets of points (t[i], x[i][j]) (where i is constant),
 /// that share the same timestamps in t. In particular, the coordinates
 /// of points to interpolate for the drawing share the same t.
+/// If the boundary is periodic and the last point is not the first one, the
+/// path is closed by coming back to the first point after the last, in the
+/// mean time between two points, the way mm gives for the last point.
+/// The system is solved once for all, in O(n) time.
+/// Fails as `interpolate` does, or if the last part is linear while the start
+/// condition of boundary needs an equation, cf `start_holds`.
 #[allow(dead_code)]
-pub fn interpolate_coords(xxx: Vec<Vec<f64>>, tt: &Vec<f64>, mm: &Vec<bool>)
- -> Vec<Spline> {
+pub fn interpolate_coords(mut xxx: Vec<Vec<f64>>, tt: &[f64], mm: &[bool],
+    boundary: &Boundary) -> Result<Vec<Spline>, SplineError> {
+    check_times(tt)?;
+    if let Boundary::Clamped(start, end) = boundary {
+        assert!(start.len() == xxx.len() && end.len() == xxx.len());
+    }
+    let points = tt.len();
+    let mut tt = tt.to_vec();
+    let mut mm = mm.to_vec();
+    if *boundary == Boundary::Periodic && xxx.iter().any(|xx| xx[0] != xx[points-1]) {
+        tt.push(tt[points-1] + (tt[points-1] - tt[0]) / (points - 1) as f64);
+        mm.push(false);
+        for xx in xxx.iter_mut() {
+            xx.push(xx[0]);
+        }
+    }
+
     let n = tt.len();
-    let count = xxx.len();
-    let a = matrix_for(&tt, &mm);
-    // println!("A = {}", a);
-    let dec = a.lu();
-
-    let mut ss = Vec::new();
-
-    for i in 0..count {
-        assert_eq!(n, xxx[i].len());
-        ss.push(  Spline {parts: Vec::new(),
-                          changes: tt.clone(),
-                          current: 0,
-                          start: tt[0],  end: tt[n-1]    });
-    
-        let mut b: DVector<f64> = DVector::zeros(4*(n-1));
-        // Building `b` vector:
-        for j in 0..(n-1) {
-            b[4*j]   = xxx[i][j];
-            b[(4*j+1)] = xxx[i][(j+1)];
-        };
-        // println!("xx: {:?} b: {}", xxx[i], b);
-
-        let x = dec.solve(&b).expect("Computation of spline's coefficients failed !");
-        // println!("(i={}) sol = {}", i, x);  
-        for j in 0..(n-1) {
-            ss[i].parts.push(SplinePart{
-                a: x[4*j],
-                b: x[4*j+1],
-                c: x[4*j+2],
-                d: x[4*j+3],
-            });
+    if mm[n-2] && !start_holds(&mm[..n-1], boundary) {
+        return Err(SplineError::LinearEnd { boundary: boundary.clone(), last: n-2 })
+    }
+    let (a, values, order) = system_for(&tt, &mm, boundary);
+
+    let bb = xxx.iter().enumerate().map(|(j, xx)| {
+        assert_eq!(n, xx.len());
+        values.iter().map(|&v| match (v, boundary) {
+            (Value::Point(i), _)                           => xx[i],
+            (Value::StartSlope, Boundary::Clamped(start, _)) => start[j],
+            (Value::EndSlope, Boundary::Clamped(_, end))     => end[j],
+            _                                              => 0.0,
+        }).collect()
+    }).collect();
+    // the unknowns of the k-th column are the coefficients of the part
+    // order[k / 4], the closing one ending at the first point
+    let solutions = a.solve(bb).map_err(|k| {
+        let part = order[k / 4];
+        SplineError::SingularSystem { first: part, last: (part + 1) % points }
+    })?;
+
+    Ok(solutions.into_iter().map(|x| {
+        let mut parts = vec![SplinePart { a: 0.0, b: 0.0, c: 0.0, d: 0.0 }; n-1];
+        for (p, &i) in order.iter().enumerate() {
+            parts[i] = SplinePart { a: x[4*p],
+                                    b: x[4*p+1],
+                                    c: x[4*p+2],
+                                    d: x[4*p+3] };
         }
-    };
-    ss
+        Spline { parts,
+                 changes: tt.clone(),
+                 current: 0,
+                 start: tt[0],  end: tt[n-1] }
+    }).collect())
 }
 
-/// Evaluates the spline at position x.
+/// Evaluates the spline at position x, finding its part by a binary search.
 #[allow(dead_code)]
 pub fn eval(spline: & Spline, x: f64) -> f64 {
     if x < spline.start || x > spline.end {return 0 as f64}
-    let mut npart = 0;
-    for c in spline.changes() {
-        if x < c {break;}
-        npart += 1;
-    }
-    eval_part(spline.parts[npart-1], x)
+    let npart = spline.changes.partition_point(|&c| c <= x);
+    // x == end belongs to the last part
+    let npart = npart.min(spline.parts.len()) - 1;
+    eval_part(spline.parts[npart], x - spline.changes[npart])
 }
 
 /// Compute value of cubic polynomial hold in the splinepart
-/// at position x.
-fn eval_part(part: SplinePart, x: f64) -> f64 {
-    part.a * x.powi(3) + part.b * x.powi(2) + part.c * x + part.d
+/// at position u from its start.
+fn eval_part(part: SplinePart, u: f64) -> f64 {
+    ((part.a * u + part.b) * u + part.c) * u + part.d
+}
+
+impl SplinePart {
+    /// Returns the slope of the polynomial at position u from its start.
+    pub fn slope(&self, u: f64) -> f64 {
+        3.0 * self.a * u * u + 2.0 * self.b * u + self.c
+    }
+
+    /// Returns the control values of the cubic Bézier curve that is equal to
+    /// the polynomial over the part, lasting h: the ends, and two values set
+    /// by the slopes at the ends.
+    pub fn bezier(&self, h: f64) -> [f64; 4] {
+        let (p1, p2) = (self.d, eval_part(*self, h));
+        [p1, p1 + h * self.c / 3.0, p2 - h * self.slope(h) / 3.0, p2]
+    }
+
+    /// Returns the polynomial of a part lasting h that is equal to the cubic
+    /// Bézier curve of control values v, the inverse of `bezier`.
+    pub fn from_bezier(v: [f64; 4], h: f64) -> SplinePart {
+        // coefficients in u / h, from 0 to 1
+        let a = v[3] - v[0] + 3.0 * (v[1] - v[2]);
+        let b = 3.0 * (v[0] - 2.0 * v[1] + v[2]);
+        let c = 3.0 * (v[1] - v[0]);
+        SplinePart { a: a / (h * h * h), b: b / (h * h), c: c / h, d: v[0] }
+    }
 }
 
 impl Iterator for Spline {
@@ -228,13 +482,13 @@ impl Iterator for Spline {
     }
 }
 
+/// Writes each part on a line, from the time t_i at which it starts, as a
+/// polynomial of u = t - t_i.
 impl fmt::Display for Spline {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let mut i: usize = 0;
-        for sp in self.parts.clone() {
-            write!(f, "Part {}) [t: {:+.4e}]  {:+.4e}x³ {:+.4e}x² {:+.4e}x {:+.4e}\n", 
+        for (i, sp) in self.parts.iter().enumerate() {
+            writeln!(f, "Part {}) [t: {:+.4e}]  {:+.4e}u³ {:+.4e}u² {:+.4e}u {:+.4e}", 
                         i+1, self.changes[i], sp.a, sp.b, sp.c, sp.d)?;
-            i += 1;
         };
         Ok(())
     }
@@ -248,4 +502,213 @@ impl Spline {
     pub fn changes(&self) -> Vec<f64> {self.changes.clone()}
     pub fn num_parts(&self) -> usize {self.parts.len()}
     pub fn eval(&self, t: f64) -> f64 {eval(self, t)}
-}
\ No newline at end of file
+    /// Returns the spline made of the cubic Bézier curves of control values
+    /// bb, the i-th one going from tt[i] to tt[i+1].
+    pub fn from_beziers(bb: &[[f64; 4]], tt: &[f64]) -> Spline {
+        assert_eq!(bb.len() + 1, tt.len());
+        Spline { parts: bb.iter().enumerate()
+                          .map(|(i, &v)| SplinePart::from_bezier(v, tt[i+1] - tt[i]))
+                          .collect(),
+                 changes: tt.to_vec(),
+                 current: 0,
+                 start: tt[0],  end: tt[tt.len()-1] }
+    }
+
+    /// Returns the control values of the Bézier curve of each part.
+    pub fn beziers(&self) -> Vec<[f64; 4]> {
+        self.parts.iter().enumerate()
+                  .map(|(i, p)| p.bezier(self.changes[i+1] - self.changes[i]))
+                  .collect()
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TT: [f64; 8] = [0.0, 1.5, 2.0, 3.5, 5.0, 6.0, 8.0, 9.5];
+    // closed, so that periodic splines are not given one more part
+    const XX: [f64; 8] = [3.0, -1.0, 4.0, 1.5, -5.0, 9.0, 2.5, 3.0];
+
+    /* Solves a x = b by gaussian elimination with partial pivoting over the
+     * whole matrix, as the dense solver used before `Banded` did */
+    fn dense_solve(mut a: Vec<Vec<f64>>, mut b: Vec<f64>) -> Vec<f64> {
+        let n = b.len();
+        for k in 0..n {
+            let p = (k..n).max_by(|&i, &j| a[i][k].abs().partial_cmp(&a[j][k].abs()).unwrap())
+                          .unwrap();
+            assert!(a[p][k] != 0.0, "singular reference system");
+            a.swap(k, p);
+            b.swap(k, p);
+            let (pivot, rest) = a.split_at_mut(k + 1);
+            for (r, row) in rest.iter_mut().enumerate() {
+                let f = row[k] / pivot[k][k];
+                for (v, p) in row[k..].iter_mut().zip(&pivot[k][k..]) {
+                    *v -= f * p;
+                }
+                b[k + 1 + r] -= f * b[k];
+            }
+        }
+        let mut x = vec![0.0; n];
+        for k in (0..n).rev() {
+            let sum: f64 = ((k + 1)..n).map(|c| a[k][c] * x[c]).sum();
+            x[k] = (b[k] - sum) / a[k][k];
+        }
+        x
+    }
+
+    /* The whole matrix of the band one */
+    fn dense(a: &Banded) -> Vec<Vec<f64>> {
+        (0..a.n).map(|r| (0..a.n).map(|c| {
+            if c + a.lower >= r && c <= r + a.upper { a.get(r, c) } else { 0.0 }
+        }).collect()).collect()
+    }
+
+    /* Checks that the parts of the spline through XX are the ones the dense
+     * reference solve gives, within a relative tolerance */
+    fn check_against_dense(mm: &[bool], boundary: Boundary) {
+        let (a, values, order) = system_for(&TT, mm, &boundary);
+        let b = values.iter().map(|&v| match (v, &boundary) {
+            (Value::Point(i), _)                             => XX[i],
+            (Value::StartSlope, Boundary::Clamped(start, _)) => start[0],
+            (Value::EndSlope, Boundary::Clamped(_, end))     => end[0],
+            _                                                => 0.0,
+        }).collect();
+        let x = dense_solve(dense(&a), b);
+
+        let spline = interpolate(&XX, &TT, mm, &boundary).unwrap();
+        assert_eq!(spline.num_parts(), TT.len() - 1);
+        let scale = x.iter().fold(1.0_f64, |m, v| m.max(v.abs()));
+        for (p, &i) in order.iter().enumerate() {
+            let part = spline.part(i);
+            for (got, want) in [part.a, part.b, part.c, part.d].iter().zip(&x[4*p..4*p+4]) {
+                assert!((got - want).abs() <= 1e-9 * scale,
+                        "{}, part {}: {} instead of {}", boundary, i, got, want);
+            }
+        }
+    }
+
+    #[test]
+    fn banded_solve_matches_dense_for_each_boundary() {
+        let mm = [false; 8];
+        for boundary in [Boundary::Quadratic, Boundary::Periodic, Boundary::Natural,
+                         Boundary::Clamped(vec![1.5], vec![-2.0]), Boundary::NotAKnot] {
+            check_against_dense(&mm, boundary);
+        }
+    }
+
+    #[test]
+    fn banded_solve_matches_dense_with_linear_parts() {
+        // the last part being linear, there is no start equation
+        let linear_end = [false, false, true, false, false, false, true, false];
+        check_against_dense(&linear_end, Boundary::Quadratic);
+        // unless the start condition holds on lines
+        let linear_ends = [true, true, false, false, false, false, true, false];
+        for boundary in [Boundary::Natural, Boundary::NotAKnot] {
+            check_against_dense(&linear_ends, boundary);
+        }
+        let mixed = [false, true, false, false, true, false, false, false];
+        check_against_dense(&mixed, Boundary::Periodic);
+    }
+
+    #[test]
+    fn start_condition_cannot_be_left_out() {
+        let linear_end = [false, false, true, false, false, false, true, false];
+        for boundary in [Boundary::Natural, Boundary::Clamped(vec![1.5], vec![-2.0]),
+                         Boundary::NotAKnot] {
+            assert!(matches!(interpolate(&XX, &TT, &linear_end, &boundary),
+                             Err(SplineError::LinearEnd { last: 6, .. })), "{}", boundary);
+        }
+        let first_line = [true, false, false, false, false, false, true, false];
+        assert!(interpolate(&XX, &TT, &first_line, &Boundary::Natural).is_ok());
+        assert!(matches!(interpolate(&XX, &TT, &first_line, &Boundary::NotAKnot),
+                         Err(SplineError::LinearEnd { .. })));
+    }
+
+    /* The part i of the spline through XX, and its duration */
+    fn part_of(spline: &Spline, i: usize) -> (SplinePart, f64) {
+        (spline.part(i), TT[i + 1] - TT[i])
+    }
+
+    fn assert_close(got: f64, want: f64, what: &str) {
+        assert!((got - want).abs() < 1e-9, "{}: {} instead of {}", what, got, want);
+    }
+
+    #[test]
+    fn natural_has_no_concavity_at_the_ends() {
+        let spline = interpolate(&XX, &TT, &[false; 8], &Boundary::Natural).unwrap();
+        let (first, _) = part_of(&spline, 0);
+        let (last, h) = part_of(&spline, TT.len() - 2);
+        assert_close(2.0 * first.b, 0.0, "start");
+        assert_close(6.0 * last.a * h + 2.0 * last.b, 0.0, "end");
+    }
+
+    #[test]
+    fn clamped_has_the_given_slopes() {
+        let boundary = Boundary::Clamped(vec![1.5], vec![-2.0]);
+        let spline = interpolate(&XX, &TT, &[false; 8], &boundary).unwrap();
+        let (first, _) = part_of(&spline, 0);
+        let (last, h) = part_of(&spline, TT.len() - 2);
+        assert_close(first.slope(0.0), 1.5, "start");
+        assert_close(last.slope(h), -2.0, "end");
+    }
+
+    #[test]
+    fn not_a_knot_has_a_continuous_third_derivative() {
+        let spline = interpolate(&XX, &TT, &[false; 8], &Boundary::NotAKnot).unwrap();
+        let n = TT.len() - 1;
+        // the third derivative of a part being 6 a
+        assert_close(spline.part(0).a, spline.part(1).a, "second knot");
+        assert_close(spline.part(n - 2).a, spline.part(n - 1).a, "second to last knot");
+    }
+
+    #[test]
+    fn periodic_ends_as_it_starts() {
+        let spline = interpolate(&XX, &TT, &[false; 8], &Boundary::Periodic).unwrap();
+        let (first, _) = part_of(&spline, 0);
+        let (last, h) = part_of(&spline, TT.len() - 2);
+        assert_close(last.slope(h), first.slope(0.0), "slope");
+        assert_close(6.0 * last.a * h + 2.0 * last.b, 2.0 * first.b, "concavity");
+    }
+
+    #[test]
+    fn quadratic_does_not_depend_on_the_time_origin() {
+        let mm = [false, false, false, false, false, false, true, false];
+        let shifted: Vec<f64> = TT.iter().map(|t| t + 1e6).collect();
+        for mm in [&[false; 8], &mm] {
+            let spline = interpolate(&XX, &TT, mm, &Boundary::Quadratic).unwrap();
+            let moved = interpolate(&XX, &shifted, mm, &Boundary::Quadratic).unwrap();
+            for i in 0..TT.len() - 1 {
+                let (p, q) = (spline.part(i), moved.part(i));
+                for (got, want) in [q.a, q.b, q.c, q.d].iter().zip([p.a, p.b, p.c, p.d]) {
+                    assert!((got - want).abs() < 1e-6, "part {}: {} instead of {}", i, got, want);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn eval_finds_the_part_of_each_time() {
+        let spline = interpolate(&XX, &TT, &[false; 8], &Boundary::Natural).unwrap();
+        for (i, &t) in TT.iter().enumerate() {
+            assert!((spline.eval(t) - XX[i]).abs() < 1e-9, "t = {}", t);
+        }
+        let part = spline.part(3);
+        let u = 0.25 * (TT[4] - TT[3]);
+        assert!((spline.eval(TT[3] + u) - ((part.a * u + part.b) * u + part.c) * u - part.d)
+                .abs() < 1e-12);
+        assert_eq!(spline.eval(TT[0] - 1.0), 0.0);
+        assert_eq!(spline.eval(TT[7] + 1.0), 0.0);
+    }
+
+    #[test]
+    fn linear_parts_are_lines_through_their_points() {
+        let mm = [false, false, true, false, false, false, true, false];
+        let spline = interpolate(&XX, &TT, &mm, &Boundary::Quadratic).unwrap();
+        for i in [2, 6] {
+            let part = spline.part(i);
+            assert!(part.a.abs() < 1e-12 && part.b.abs() < 1e-12);
+            let slope = (XX[i+1] - XX[i]) / (TT[i+1] - TT[i]);
+            assert!((part.c - slope).abs() < 1e-12 && (part.d - XX[i]).abs() < 1e-12);
+        }
+    }
+}
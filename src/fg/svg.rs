@@ -0,0 +1,211 @@
+use std::fs::File;
+use std::io::{Error, Write};
+use std::f64::consts::PI;
+
+use super::fourier::CoeffsSet;
+use super::complex::Complex;
+use super::color::Rgb;
+use super::fgif::*;
+use super::figure::{Figure, Item};
+
+/// Timing of the animations of a SVG file, that all last one cycle of the
+/// drawing and its pause.
+/// Members:
+///
+/// - `dur`     : duration of a cycle, in seconds
+/// - `drawn`   : part of the cycle during which the figure is drawn, the rest
+///               being the pause
+/// - `repeat`  : value of the `repeatCount` attribute
+#[allow(clippy::doc_overindented_list_items)]
+struct Timing {
+    dur: f64,
+    drawn: f64,
+    repeat: String,
+}
+
+impl Timing {
+    fn new(opts: &DrawOptions) -> Timing {
+        let frames = (opts.n_steps + opts.pause) as f64;
+        Timing {
+            dur: frames / opts.fps,
+            drawn: opts.n_steps as f64 / frames,
+            repeat: if opts.loops == 0 { String::from("indefinite") }
+                    else               { opts.loops.to_string()     },
+        }
+    }
+
+    /// Returns the attributes of an animation going linearly through values,
+    /// evenly spaced along the drawing, then staying at the last one during
+    /// the pause.
+    fn keyframes(&self, values: &[String]) -> String {
+        let n = values.len() - 1;
+        let mut values = values.to_vec();
+        let mut times: Vec<String> = (0..=n)
+            .map(|i| num(self.drawn * i as f64 / n as f64, 6))
+            .collect();
+        if self.drawn < 1.0 {
+            values.push(values[n].clone());
+            times.push(String::from("1"));
+        }
+        format!("values=\"{}\" keyTimes=\"{}\" dur=\"{}s\" repeatCount=\"{}\" fill=\"freeze\"",
+            values.join(";"), times.join(";"), num(self.dur, 3), self.repeat)
+    }
+}
+
+/// Writes in file the animation of the figure represented by the Fourier
+/// coefficients in coeffs, as an animated SVG, following the same options as
+/// `fgif::draw_fourier_coeff`.
+/// Each epicycle is a group, rotating within the group of the previous one:
+/// the viewer does the animation, so that the file only holds the terms and
+/// the traced drawing, which is revealed with its `stroke-dashoffset` as the
+/// tip goes. Animations use SMIL, and the size of the output is only the one
+/// of the `viewBox`: it can be scaled at will.
+pub fn draw_fourier_coeff(coeffs: CoeffsSet, marks: &[Complex], mut file: File,
+    t_span: (f64, f64), opts: &DrawOptions, palette: &Palette) -> Result<DrawReport, Error> {
+
+    let terms = ordered_terms(&coeffs, opts.order);
+    let (anchor, offset) = match opts.anchor {
+        Anchor::Dc     => (coeffs.dc(),     Complex::zero()),
+        Anchor::Center => (Complex::zero(), Complex::zero() - coeffs.dc()),
+    };
+    let marks: Vec<Complex> = if opts.show_points { marks.iter().map(|&m| m + offset).collect() }
+                              else                { Vec::new() };
+
+    let n_steps = opts.n_steps.max(1);
+    let period = t_span.1 - t_span.0;
+    let omega0 = 2.0 * PI / period;
+    // the traced drawing goes back to its start
+    let times: Vec<f64> = (0..=n_steps)
+        .map(|i| t_span.0 + period * i as f64 / n_steps as f64)
+        .collect();
+    let view = Viewport::new(&epicycles_bbox(anchor, &terms, omega0, &times, &marks,
+        opts.circles), opts);
+
+    let mut palette = palette.clone();
+    let inks = term_inks(&terms, opts.scheme, &mut palette);
+    let timing = Timing::new(opts);
+    let marker = (opts.trace_width.max(opts.arm_width) + 2.0) / 2.0;
+
+    let mut svg = format!("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" \
+        height=\"{h}\" viewBox=\"0 0 {w} {h}\">\n", w = opts.width, h = opts.height);
+    svg += &format!("<rect width=\"100%\" height=\"100%\" fill=\"{}\"/>\n",
+        hex(palette.color(BACKGROUND)));
+
+    for m in marks.iter() {
+        let (x, y) = view.to_pixel(*m);
+        svg += &format!("<circle cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"{}\"/>\n",
+            num(x, 2), num(y, 2), num(marker, 2), hex(palette.color(POINTS)));
+    }
+
+    // the traced drawing, hidden by a gap as long as itself, that slides away
+    // as the tip goes
+    let tips: Vec<(f64, f64)> = times.iter()
+        .map(|&t| view.to_pixel(chain(anchor, &terms, omega0, t)[terms.len()]))
+        .collect();
+    let mut lengths = vec![0.0];
+    for i in 1..tips.len() {
+        let d = (tips[i].0 - tips[i-1].0).hypot(tips[i].1 - tips[i-1].1);
+        lengths.push(lengths[i-1] + d);
+    }
+    let dash = lengths[lengths.len() - 1] + 1.0;
+    let path: Vec<String> = tips.iter()
+        .map(|&(x, y)| format!("{},{}", num(x, 2), num(y, 2)))
+        .collect();
+    let offsets: Vec<String> = lengths.iter().map(|l| num(dash - l, 2)).collect();
+    svg += &format!("<path d=\"M{}\" fill=\"none\" stroke=\"{}\" stroke-width=\"{}\" \
+        stroke-linecap=\"round\" stroke-linejoin=\"round\" stroke-dasharray=\"{d}\" \
+        stroke-dashoffset=\"{d}\">\n", path.join(" "), hex(palette.color(TRACE)),
+        num(opts.trace_width, 2), d = num(dash, 2));
+    svg += &format!("<animate attributeName=\"stroke-dashoffset\" {}/>\n</path>\n",
+        timing.keyframes(&offsets));
+
+    // the epicycles: each group is attached to the tip of the previous arm,
+    // and rotates relatively to it
+    let (ax, ay) = view.to_pixel(anchor);
+    svg += &format!("<g fill=\"none\" stroke-width=\"{}\" stroke-linecap=\"round\" \
+        transform=\"translate({},{})\">\n", num(opts.arm_width, 2), num(ax, 2), num(ay, 2));
+    let (mut previous_k, mut previous_arg) = (0, 0.0);
+    for (i, &(k, c)) in terms.iter().enumerate() {
+        let r = c.modulus() * view.scale;
+        let arg = c.arg() + k as f64 * omega0 * t_span.0;
+        // the y axis being flipped, rotations go the other way
+        let start = -(arg - previous_arg).to_degrees();
+        let end = start - 360.0 * (k - previous_k) as f64;
+        if i > 0 {
+            let r_previous = terms[i-1].1.modulus() * view.scale;
+            svg += &format!("<g transform=\"translate({},0)\">\n", num(r_previous, 2));
+        }
+        svg += &format!("<g><animateTransform attributeName=\"transform\" type=\"rotate\" {}/>\n",
+            timing.keyframes(&[num(start, 3), num(end, 3)]));
+        if opts.circles && r >= 0.5 {
+            svg += &format!("<circle r=\"{}\" stroke=\"{}\" stroke-width=\"1\"/>\n",
+                num(r, 2), hex(palette.color(inks[i].1)));
+        }
+        svg += &format!("<line x2=\"{}\" stroke=\"{}\"/>\n", num(r, 2),
+            hex(palette.color(inks[i].0)));
+        previous_k = k;
+        previous_arg = arg;
+    }
+    let r_last = terms.last().map_or(0.0, |&(_, c)| c.modulus() * view.scale);
+    svg += &format!("<circle cx=\"{}\" r=\"{}\" fill=\"{}\"/>\n", num(r_last, 2),
+        num(marker, 2), hex(palette.color(TIP)));
+    for i in 0..terms.len() {
+        svg += if i > 0 { "</g>\n</g>\n" } else { "</g>\n" };
+    }
+    svg += "</g>\n</svg>\n";
+
+    file.write_all(svg.as_bytes())?;
+    Ok(DrawReport { offset, bytes: svg.len(), full_bytes: None })
+}
+
+/// Writes the still drawing fig in file, and returns the number of bytes
+/// written.
+pub fn write_figure(fig: &Figure, mut file: File) -> Result<usize, Error> {
+    let point = |p: (f64, f64)| format!("{},{}", num(p.0, 2), num(p.1, 2));
+
+    let mut svg = format!("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" \
+        height=\"{h}\" viewBox=\"0 0 {w} {h}\">\n", w = fig.width, h = fig.height);
+    svg += &format!("<rect width=\"100%\" height=\"100%\" fill=\"{}\"/>\n",
+        hex(fig.background));
+    svg += "<g fill=\"none\" stroke-linecap=\"round\" stroke-linejoin=\"round\">\n";
+    for item in fig.items.iter() {
+        match item {
+            Item::Curve { start, curves, color, width } => {
+                let d: Vec<String> = curves.iter()
+                    .map(|c| format!("{} {} {}", point(c[0]), point(c[1]), point(c[2])))
+                    .collect();
+                svg += &format!("<path d=\"M{} C{}\" stroke=\"{}\" stroke-width=\"{}\"/>\n",
+                    point(*start), d.join(" "), hex(*color), num(*width, 2));
+            }
+            Item::Polyline { points, color, width } => {
+                let points: Vec<String> = points.iter().map(|&p| point(p)).collect();
+                svg += &format!("<polyline points=\"{}\" stroke=\"{}\" stroke-width=\"{}\"/>\n",
+                    points.join(" "), hex(*color), num(*width, 2));
+            }
+            Item::Disc { center, radius, color } => {
+                svg += &format!("<circle cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"{}\"/>\n",
+                    num(center.0, 2), num(center.1, 2), num(*radius, 2), hex(*color));
+            }
+        }
+    }
+    svg += "</g>\n</svg>\n";
+
+    file.write_all(svg.as_bytes())?;
+    Ok(svg.len())
+}
+
+/* Returns the colour c as `#rrggbb` */
+fn hex(c: Rgb) -> String {
+    format!("#{:02x}{:02x}{:02x}", c.0, c.1, c.2)
+}
+
+/// Returns x written with at most the given number of decimals, without the
+/// trailing zeros, to keep the file small.
+pub fn num(x: f64, decimals: usize) -> String {
+    let s = format!("{:.*}", decimals, x);
+    let s = if s.contains('.') { s.trim_end_matches('0').trim_end_matches('.') } else { &s };
+    match s {
+        "-0" => String::from("0"),
+        _    => String::from(s),
+    }
+}
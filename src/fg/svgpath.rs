@@ -0,0 +1,517 @@
+use std::f64::consts::PI;
+
+use super::complex::Complex;
+use super::read::{self, PointsSet, ReadingError};
+use super::spline::Spline;
+
+/// Distance under which two points of a path are the same.
+const EPSILON: f64 = 1e-9;
+
+/// Number of chords measuring the length of a segment, that sets the time
+/// spent on it.
+const LENGTH_STEPS: usize = 16;
+
+/// Piece of a path: a cubic Bézier curve, given by its ends and its control
+/// points, that is a straight line if `line`.
+#[derive(Clone, Copy, Debug)]
+pub struct Segment {
+    pub points: [Complex; 4],
+    pub line: bool,
+}
+
+impl Segment {
+    fn line(p0: Complex, p3: Complex) -> Segment {
+        let d = (p3 - p0) / 3.0;
+        Segment { points: [p0, p0 + d, p3 - d, p3], line: true }
+    }
+
+    fn cubic(p0: Complex, p1: Complex, p2: Complex, p3: Complex) -> Segment {
+        Segment { points: [p0, p1, p2, p3], line: false }
+    }
+
+    /* Point of the curve at u, from 0 to 1 */
+    fn eval(&self, u: f64) -> Complex {
+        let v = 1.0 - u;
+        let p = self.points;
+        p[0] * (v * v * v) + p[1] * (3.0 * v * v * u) + p[2] * (3.0 * v * u * u)
+            + p[3] * (u * u * u)
+    }
+
+    /// Returns the length of the curve, measured along chords.
+    pub fn length(&self) -> f64 {
+        (0..LENGTH_STEPS).map(|i| (self.eval((i + 1) as f64 / LENGTH_STEPS as f64)
+                                   - self.eval(i as f64 / LENGTH_STEPS as f64)).modulus())
+                         .sum()
+    }
+}
+
+/// Reads the `<path>` elements of the SVG file named filename, and returns
+/// their segments, joined by straight lines into a single closed curve, cf
+/// `connect`.
+pub fn read_svg(filename: &str) -> Result<Vec<Segment>, ReadingError> {
+    let data = read::read_to_string(filename)?;
+    let mut segments = Vec::new();
+    for d in path_data(&data) {
+        segments.extend(parse_path(&d)?);
+    }
+    let segments = connect(segments);
+    if segments.is_empty() {
+        return Err(ReadingError::NotEnoughPoints)
+    }
+    Ok(segments)
+}
+
+/// Returns the `d` attribute of the `<path>` elements of the SVG document
+/// svg, in the order they are written. Transforms are not applied.
+pub fn path_data(svg: &str) -> Vec<String> {
+    let mut data = Vec::new();
+    let mut rest = svg;
+    while let Some(i) = rest.find("<path") {
+        rest = &rest[i + 5..];
+        let end = rest.find('>').unwrap_or(rest.len());
+        let tag = &rest[..end];
+        if tag.starts_with(|c: char| c.is_whitespace() || c == '/' || c == '>') {
+            if let Some(d) = attribute(tag, "d") {
+                data.push(String::from(d));
+            }
+        }
+        rest = &rest[end..];
+    }
+    data
+}
+
+/* Returns the value of the attribute name in the text of a tag */
+fn attribute<'a>(tag: &'a str, name: &str) -> Option<&'a str> {
+    let mut rest = tag;
+    while let Some(i) = rest.find(name) {
+        let before = tag.len() - rest.len() + i;
+        let after = rest[i + name.len()..].trim_start();
+        rest = &rest[i + name.len()..];
+        if before > 0 && !tag[..before].ends_with(char::is_whitespace) {
+            continue
+        }
+        if !after.starts_with('=') {
+            continue
+        }
+        let value = after[1..].trim_start();
+        let quote = match value.chars().next() {
+            Some(q) if q == '"' || q == '\'' => q,
+            _ => continue,
+        };
+        return value[1..].find(quote).map(|end| &value[1..end + 1])
+    }
+    None
+}
+
+/// Returns the segments drawn by the path data d, in the absolute coordinates
+/// of the SVG document, its y axis pointing down. Quadratic curves and
+/// elliptical arcs are turned into cubic curves, exactly for the former.
+/// see https://www.w3.org/TR/SVG11/paths.html#PathData
+pub fn parse_path(d: &str) -> Result<Vec<Segment>, ReadingError> {
+    let mut p = PathParser { text: d.as_bytes(), pos: 0 };
+    let mut segments = Vec::new();
+
+    let mut command: Option<u8> = None;
+    let (mut current, mut start) = (Complex::zero(), Complex::zero());
+    // second control point of the last curve, for the smooth ones
+    let mut last = Last::Other;
+
+    loop {
+        p.skip_separators();
+        let c = match p.peek() {
+            None => break,
+            Some(c) if c.is_ascii_alphabetic() => {
+                p.pos += 1;
+                c
+            }
+            // the previous command is repeated, moves being followed by lines
+            Some(_) => match command {
+                Some(b'M') => b'L',
+                Some(b'm') => b'l',
+                Some(c) if c != b'Z' && c != b'z' => c,
+                _ => return Err(p.error("expected a command")),
+            },
+        };
+        command = Some(c);
+
+        let base = if c.is_ascii_lowercase() { current } else { Complex::zero() };
+        let mut next = Last::Other;
+        match c.to_ascii_uppercase() {
+            b'M' => {
+                current = base + p.point()?;
+                start = current;
+            }
+            b'L' => {
+                let end = base + p.point()?;
+                segments.push(Segment::line(current, end));
+                current = end;
+            }
+            b'H' => {
+                let end = Complex { re: base.re + p.number()?, im: current.im };
+                segments.push(Segment::line(current, end));
+                current = end;
+            }
+            b'V' => {
+                let end = Complex { re: current.re, im: base.im + p.number()? };
+                segments.push(Segment::line(current, end));
+                current = end;
+            }
+            b'C' | b'S' => {
+                let c1 = match (c.to_ascii_uppercase(), last) {
+                    (b'C', _)             => base + p.point()?,
+                    (_, Last::Cubic(c2))  => current * 2.0 - c2,
+                    _                     => current,
+                };
+                let c2 = base + p.point()?;
+                let end = base + p.point()?;
+                segments.push(Segment::cubic(current, c1, c2, end));
+                current = end;
+                next = Last::Cubic(c2);
+            }
+            b'Q' | b'T' => {
+                let q = match (c.to_ascii_uppercase(), last) {
+                    (b'Q', _)            => base + p.point()?,
+                    (_, Last::Quad(q))   => current * 2.0 - q,
+                    _                    => current,
+                };
+                let end = base + p.point()?;
+                segments.push(Segment::cubic(current, current + (q - current) * (2.0 / 3.0),
+                    end + (q - end) * (2.0 / 3.0), end));
+                current = end;
+                next = Last::Quad(q);
+            }
+            b'A' => {
+                let (rx, ry) = (p.number()?, p.number()?);
+                let rotation = p.number()?;
+                let (large, sweep) = (p.flag()?, p.flag()?);
+                let end = base + p.point()?;
+                segments.extend(arc(current, end, rx, ry, rotation, large, sweep));
+                current = end;
+            }
+            b'Z' => {
+                if (start - current).modulus() > EPSILON {
+                    segments.push(Segment::line(current, start));
+                }
+                current = start;
+            }
+            _ => return Err(p.error("unknown command")),
+        }
+        last = next;
+    }
+    Ok(segments)
+}
+
+/// Returns the segments, skipping the ones of length zero, and joining by
+/// straight lines the ends and starts that are apart, as subpaths or paths
+/// are, so that they are drawn as a single curve. The curve is closed the
+/// same way, going back to its start, as Fourier series are periodic.
+pub fn connect(segments: Vec<Segment>) -> Vec<Segment> {
+    let mut connected: Vec<Segment> = Vec::with_capacity(segments.len() + 1);
+    for s in segments.into_iter().filter(|s| s.length() > EPSILON) {
+        if let Some(previous) = connected.last() {
+            let end = previous.points[3];
+            if (s.points[0] - end).modulus() > EPSILON {
+                connected.push(Segment::line(end, s.points[0]));
+            }
+        }
+        connected.push(s);
+    }
+    if let (Some(first), Some(last)) = (connected.first(), connected.last()) {
+        let (start, end) = (first.points[0], last.points[3]);
+        if (start - end).modulus() > EPSILON {
+            connected.push(Segment::line(end, start));
+        }
+    }
+    connected
+}
+
+/// Returns the times at which the drawing reaches the ends of the segments,
+/// the time spent on each being its length.
+pub fn times(segments: &[Segment]) -> Vec<f64> {
+    let mut tt = vec![0.0];
+    for s in segments {
+        tt.push(tt[tt.len() - 1] + s.length());
+    }
+    tt
+}
+
+/// Returns the splines of the x and y coordinates of the segments, that are
+/// exactly the curves of the path, y pointing up, cf `times`.
+pub fn splines(segments: &[Segment]) -> (Spline, Spline) {
+    let tt = times(segments);
+    let coords = |f: &dyn Fn(Complex) -> f64| segments.iter()
+        .map(|s| [f(s.points[0]), f(s.points[1]), f(s.points[2]), f(s.points[3])])
+        .collect::<Vec<[f64; 4]>>();
+    (Spline::from_beziers(&coords(&|p| p.re), &tt),
+     Spline::from_beziers(&coords(&|p| -p.im), &tt))
+}
+
+/// Returns the ends of the segments, with the interpolation after them, that
+/// is linear after the start of straight lines, y pointing up. Interpolating
+/// them only gives back the curves approximately, unlike `splines`.
+pub fn points(segments: &[Segment]) -> PointsSet {
+    let mut set = PointsSet { xx: Vec::new(), yy: Vec::new(), tt: times(segments),
+                              mm: Vec::new(), boundary: None };
+    for s in segments {
+        set.xx.push(s.points[0].re);
+        set.yy.push(-s.points[0].im);
+        set.mm.push(s.line);
+    }
+    if let Some(s) = segments.last() {
+        set.xx.push(s.points[3].re);
+        set.yy.push(-s.points[3].im);
+        set.mm.push(false);
+    }
+    set
+}
+
+/* The curve before the current command, for the control point of the smooth
+ * curves */
+#[derive(Clone, Copy)]
+enum Last {
+    Cubic(Complex),
+    Quad(Complex),
+    Other,
+}
+
+/* Returns cubic curves following the elliptical arc from p1 to p2, of radii
+ * rx and ry, the x axis of the ellipse being rotated by rotation degrees.
+ * see https://www.w3.org/TR/SVG11/implnote.html#ArcImplementationNotes */
+fn arc(p1: Complex, p2: Complex, rx: f64, ry: f64, rotation: f64, large: bool, sweep: bool)
+    -> Vec<Segment> {
+    if (p2 - p1).modulus() <= EPSILON {
+        return Vec::new()
+    }
+    let (mut rx, mut ry) = (rx.abs(), ry.abs());
+    if rx <= EPSILON || ry <= EPSILON {
+        return vec![Segment::line(p1, p2)]
+    }
+
+    // in the axes of the ellipse, from the middle of the chord
+    let phi = rotation.to_radians();
+    let h = (p1 - p2) * Complex::expj(-phi) / 2.0;
+    let lambda = (h.re / rx).powi(2) + (h.im / ry).powi(2);
+    if lambda > 1.0 {
+        rx *= lambda.sqrt();
+        ry *= lambda.sqrt();
+    }
+    let num = (rx * ry).powi(2) - (rx * h.im).powi(2) - (ry * h.re).powi(2);
+    let den = (rx * h.im).powi(2) + (ry * h.re).powi(2);
+    let sign = if large == sweep { -1.0 } else { 1.0 };
+    let coef = sign * (num.max(0.0) / den).sqrt();
+    let c = Complex { re: coef * rx * h.im / ry, im: -coef * ry * h.re / rx };
+    let center = c * Complex::expj(phi) + (p1 + p2) / 2.0;
+
+    let theta1 = Complex { re: (h.re - c.re) / rx, im: (h.im - c.im) / ry }.arg();
+    let theta2 = Complex { re: (-h.re - c.re) / rx, im: (-h.im - c.im) / ry }.arg();
+    let mut delta = theta2 - theta1;
+    if sweep && delta < 0.0       { delta += 2.0 * PI }
+    else if !sweep && delta > 0.0 { delta -= 2.0 * PI }
+
+    // pieces of at most a quarter of ellipse
+    let n = (delta.abs() / (PI / 2.0) - EPSILON).ceil().max(1.0) as usize;
+    let step = delta / n as f64;
+    let k = 4.0 / 3.0 * (step / 4.0).tan();
+    let on_ellipse = |u: Complex| center + Complex { re: rx * u.re, im: ry * u.im }
+                                           * Complex::expj(phi);
+    (0..n).map(|i| {
+        let (a, b) = (theta1 + step * i as f64, theta1 + step * (i + 1) as f64);
+        let (ua, ub) = (Complex::expj(a), Complex::expj(b));
+        let ta = Complex { re: -ua.im, im: ua.re } * k;
+        let tb = Complex { re: -ub.im, im: ub.re } * k;
+        let start = if i == 0     { p1 } else { on_ellipse(ua) };
+        let end   = if i == n - 1 { p2 } else { on_ellipse(ub) };
+        Segment::cubic(start, on_ellipse(ua + ta), on_ellipse(ub - tb), end)
+    }).collect()
+}
+
+/* Reads the numbers of path data, that are separated by white spaces and
+ * commas, if needed only */
+struct PathParser<'a> {
+    text: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> PathParser<'a> {
+    fn error(&self, message: &str) -> ReadingError {
+        ReadingError::PathError(format!("{} at character {} of the path data", message,
+            self.pos + 1))
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.text.get(self.pos).copied()
+    }
+
+    fn skip_separators(&mut self) {
+        while let Some(b' ') | Some(b'\t') | Some(b'\n') | Some(b'\r') | Some(b',')
+                = self.peek() {
+            self.pos += 1;
+        }
+    }
+
+    fn number(&mut self) -> Result<f64, ReadingError> {
+        self.skip_separators();
+        let start = self.pos;
+        if let Some(b'+') | Some(b'-') = self.peek() {
+            self.pos += 1;
+        }
+        // a second point starts another number, as in `0.5.5`
+        let mut point = false;
+        while let Some(b) = self.peek() {
+            match b {
+                b'0'..=b'9' => {}
+                b'.' if !point => point = true,
+                _ => break,
+            }
+            self.pos += 1;
+        }
+        if let Some(b'e') | Some(b'E') = self.peek() {
+            let mark = self.pos;
+            self.pos += 1;
+            if let Some(b'+') | Some(b'-') = self.peek() {
+                self.pos += 1;
+            }
+            let digits = self.pos;
+            while let Some(b'0'..=b'9') = self.peek() {
+                self.pos += 1;
+            }
+            if self.pos == digits {
+                self.pos = mark;
+            }
+        }
+        std::str::from_utf8(&self.text[start..self.pos]).ok()
+            .and_then(|s| s.parse::<f64>().ok())
+            .ok_or_else(|| PathParser { text: self.text, pos: start }.error("expected a number"))
+    }
+
+    fn point(&mut self) -> Result<Complex, ReadingError> {
+        Ok(Complex { re: self.number()?, im: self.number()? })
+    }
+
+    /* Flags of arcs are a single digit, that may not be separated */
+    fn flag(&mut self) -> Result<bool, ReadingError> {
+        self.skip_separators();
+        let flag = match self.peek() {
+            Some(b'0') => false,
+            Some(b'1') => true,
+            _ => return Err(self.error("expected a flag")),
+        };
+        self.pos += 1;
+        Ok(flag)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /* Returns the points of the segments of the path data d, as they are
+     * parsed */
+    fn parsed(d: &str) -> Vec<[(f64, f64); 4]> {
+        parse_path(d).ok().unwrap().iter()
+                     .map(|s| s.points.map(|p| (p.re, p.im)))
+                     .collect()
+    }
+
+    fn assert_close(got: (f64, f64), want: (f64, f64)) {
+        assert!((got.0 - want.0).abs() < 1e-9 && (got.1 - want.1).abs() < 1e-9,
+                "{:?} instead of {:?}", got, want);
+    }
+
+    /* Returns the ends of the segments of the path data d, once connected */
+    fn ends(d: &str) -> Vec<(f64, f64)> {
+        let segments = connect(parse_path(d).ok().unwrap());
+        let mut ends = vec![(segments[0].points[0].re, segments[0].points[0].im)];
+        ends.extend(segments.iter().map(|s| (s.points[3].re, s.points[3].im)));
+        ends
+    }
+
+    #[test]
+    fn connect_closes_an_open_path() {
+        assert_eq!(ends("M 0 0 L 1 0 L 1 1"),
+                   vec![(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 0.0)]);
+        assert_eq!(ends("M 0 0 L 1 0 L 1 1 Z"),
+                   vec![(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 0.0)]);
+    }
+
+    #[test]
+    fn relative_commands_start_from_the_current_point() {
+        let absolute = parsed("M 1 2 L 4 2 V 6 H 1 C 1 7 2 8 3 8 Z L 1 0");
+        let relative = parsed("m 1 2 l 3 0 v 4 h -3 c 0 1 1 2 2 2 z l 0 -2");
+        assert_eq!(relative, absolute);
+        assert_eq!(absolute.last().unwrap()[3], (1.0, 0.0));
+    }
+
+    #[test]
+    fn commands_are_repeated_by_their_numbers() {
+        assert_eq!(parsed("M 0 0 L 1 0 2 0"), parsed("M 0 0 L 1 0 L 2 0"));
+        // moves being followed by lines
+        assert_eq!(parsed("M 0 0 1 0 1 1"), parsed("M 0 0 L 1 0 L 1 1"));
+        assert_eq!(parsed("m 1 1 2 0 0 2"), parsed("M 1 1 L 3 1 L 3 3"));
+        assert_eq!(parsed("M0-1.5.5.5"), parsed("M 0 -1.5 L 0.5 0.5"));
+        assert_eq!(parsed("M 0 0 c 1 1 2 1 3 0 1 1 2 1 3 0"),
+                   parsed("M 0 0 C 1 1 2 1 3 0 C 4 1 5 1 6 0"));
+        assert!(parse_path("M 0 0 Z 1 1").is_err());
+        assert!(parse_path("1 1").is_err());
+    }
+
+    #[test]
+    fn smooth_curves_reflect_the_last_control_point() {
+        let s = parsed("M 0 0 C 1 1 2 1 3 0 S 5 -1 6 0");
+        assert_eq!(s[1], [(3.0, 0.0), (4.0, -1.0), (5.0, -1.0), (6.0, 0.0)]);
+        // but for the control point of an other kind of curve
+        let s = parsed("M 0 0 L 3 0 S 5 -1 6 0");
+        assert_eq!(s[1], [(3.0, 0.0), (3.0, 0.0), (5.0, -1.0), (6.0, 0.0)]);
+
+        // quadratic curves, whose control point is at 2/3 of the cubic ones
+        let t = parsed("M 0 0 Q 1 3 2 0 T 4 0");
+        assert_close(t[0][1], (2.0 / 3.0, 2.0));
+        assert_close(t[1][1], (2.0 + 2.0 / 3.0, -2.0));
+        assert_close(t[1][2], (4.0 - 2.0 / 3.0, -2.0));
+        // a quadratic curve whose control point is its start is a line
+        let t = parsed("M 0 0 C 1 1 2 1 3 0 T 4 0");
+        assert_close(t[1][1], (3.0, 0.0));
+        assert_close(t[1][2], (3.0 + 1.0 / 3.0, 0.0));
+    }
+
+    #[test]
+    fn arcs_follow_their_ellipse() {
+        let on_circle = |d: &str, center: (f64, f64), r: f64, n: usize| {
+            let segments = parse_path(d).ok().unwrap();
+            assert_eq!(segments.len(), n, "{}", d);
+            for s in segments.iter() {
+                for i in 0..=8 {
+                    let p = s.eval(i as f64 / 8.0);
+                    let d = (p.re - center.0).hypot(p.im - center.1);
+                    assert!((d - r).abs() < 1e-3 * r, "{} at {}: {}", d, i, r);
+                }
+            }
+            segments
+        };
+        // half a circle, then three quarters of one with the large flag
+        let half = on_circle("M 1 0 A 1 1 0 0 1 -1 0", (0.0, 0.0), 1.0, 2);
+        assert!(half[0].eval(0.5).im > 0.0);
+        let large = on_circle("M 1 0 A 1 1 0 1 0 0 1", (0.0, 0.0), 1.0, 3);
+        assert!(large[1].eval(0.5).im < 0.0);
+        // radii too small to join the ends are scaled up
+        on_circle("M 0 0 A 0.1 0.1 0 0 1 2 0", (1.0, 0.0), 1.0, 2);
+        // ellipses, rotated by 90 degrees
+        let ellipse = parse_path("M 0 -2 A 2 1 90 0 1 0 2").ok().unwrap();
+        assert_close((ellipse[0].eval(1.0).re, ellipse[0].eval(1.0).im), (1.0, 0.0));
+        // and null radii draw a line
+        assert_eq!(parsed("M 0 0 A 0 1 0 0 1 2 0"), parsed("M 0 0 L 2 0"));
+    }
+
+    #[test]
+    fn subpaths_are_joined_into_one_curve() {
+        assert_eq!(ends("M 0 0 L 1 0 M 5 5 L 6 5"),
+                   vec![(0.0, 0.0), (1.0, 0.0), (5.0, 5.0), (6.0, 5.0), (0.0, 0.0)]);
+        // a closed subpath taking its start back as current point
+        assert_eq!(ends("M 1 1 l 1 0 l 0 1 z m 0 2 l 1 0"),
+                   vec![(1.0, 1.0), (2.0, 1.0), (2.0, 2.0), (1.0, 1.0), (1.0, 3.0),
+                        (2.0, 3.0), (1.0, 1.0)]);
+        let svg = r#"<svg><path d="M 0 0 L 1 0"/><pathology d="M 9 9"/>
+                     <path id="p" d='M 2 2 L 3 3'></path></svg>"#;
+        assert_eq!(path_data(svg), vec!["M 0 0 L 1 0", "M 2 2 L 3 3"]);
+    }
+}
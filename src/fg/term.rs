@@ -0,0 +1,149 @@
+use std::fs::File;
+use std::io::{Error, Write};
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use super::fgif::{Palette, BACKGROUND};
+use super::sink::FrameSink;
+
+/// Width and height, in pixels, of a braille character.
+pub const CELL: (usize, usize) = (2, 4);
+
+/// Default size of the preview, in characters, when the terminal does not
+/// tell it.
+const DEF_COLUMNS: usize = 80;
+const DEF_LINES: usize = 24;
+
+/* Bit of the braille pattern of each dot of a cell, indexed by [y][x].
+ * see https://en.wikipedia.org/wiki/Braille_Patterns */
+const DOTS: [[u32; 2]; 4] = [[0x01, 0x08], [0x02, 0x10], [0x04, 0x20], [0x40, 0x80]];
+
+/// Returns the text showing the frame t, of size w x h, each character being
+/// a braille cell of 2 x 4 pixels, whose dots are raised where the pixels are
+/// not the background. Lines end with `'\n'`.
+pub fn braille(t: &[u8], w: usize, h: usize) -> String {
+    let (columns, lines) = (w.div_ceil(CELL.0), h.div_ceil(CELL.1));
+    let mut text = String::with_capacity(lines * (3 * columns + 1));
+    for line in 0..lines {
+        for column in 0..columns {
+            let mut bits = 0;
+            for (dy, row) in DOTS.iter().enumerate() {
+                for (dx, bit) in row.iter().enumerate() {
+                    let (x, y) = (column * CELL.0 + dx, line * CELL.1 + dy);
+                    if x < w && y < h && t[y*w + x] != BACKGROUND {
+                        bits |= bit;
+                    }
+                }
+            }
+            text.push(std::char::from_u32(0x2800 + bits).unwrap());
+        }
+        text.push('\n');
+    }
+    text
+}
+
+/// Returns the size of the terminal, in characters. The `COLUMNS` and `LINES`
+/// environment variables win if they are set, as shells seldom export them;
+/// otherwise it is asked to `stty`, and a common size is assumed if that
+/// fails too.
+pub fn size() -> (usize, usize) {
+    let var = |name: &str| std::env::var(name).ok()
+                                              .and_then(|s| s.parse::<usize>().ok())
+                                              .filter(|&n| n > 0);
+    let (columns, lines) = tty_size().unzip();
+    (var("COLUMNS").or(columns).unwrap_or(DEF_COLUMNS),
+     var("LINES").or(lines).unwrap_or(DEF_LINES))
+}
+
+/* Returns the size of the controlling terminal as (columns, lines), from the
+ * output `lines columns` of `stty size`. */
+fn tty_size() -> Option<(usize, usize)> {
+    let output = Command::new("stty").arg("size")
+                                     .stdin(File::open("/dev/tty").ok()?)
+                                     .stderr(Stdio::null())
+                                     .output().ok()?;
+    if !output.status.success() { return None }
+    let text = String::from_utf8(output.stdout).ok()?;
+    let mut fields = text.split_whitespace().map(|s| s.parse::<usize>().ok().filter(|&n| n > 0));
+    let lines = fields.next()??;
+    let columns = fields.next()??;
+    Some((columns, lines))
+}
+
+/// Plays the frames in a terminal, drawn with braille characters, at the
+/// frame rate of the animation.
+pub struct Preview<W: Write> {
+    out: W,
+    width: usize,
+    height: usize,
+    fps: f64,
+    /// When the next frame is due
+    next: Option<Instant>,
+}
+
+impl<W: Write> Preview<W> {
+    pub fn new(out: W, fps: f64) -> Preview<W> {
+        Preview { out, width: 0, height: 0, fps, next: None }
+    }
+
+    /// Waits until the next frame is due.
+    fn wait(&self) {
+        if let Some(next) = self.next {
+            let now = Instant::now();
+            if next > now {
+                thread::sleep(next - now);
+            }
+        }
+    }
+}
+
+impl<W: Write> FrameSink for Preview<W> {
+    fn start(&mut self, width: usize, height: usize, _palette: &Palette) -> Result<(), Error> {
+        self.width = width;
+        self.height = height;
+        // clears the screen and hides the cursor
+        self.out.write_all(b"\x1b[2J\x1b[?25l")?;
+        Ok(())
+    }
+
+    fn write_frame(&mut self, t: &[u8], n: usize) -> Result<(), Error> {
+        self.wait();
+        self.next = Some(Instant::now() + Duration::from_secs_f64(n as f64 / self.fps));
+
+        // each frame is written over the previous one, from the top left corner
+        self.out.write_all(b"\x1b[H")?;
+        self.out.write_all(braille(t, self.width, self.height).as_bytes())?;
+        self.out.flush()
+    }
+
+    fn finish(&mut self) -> Result<(usize, Option<usize>), Error> {
+        self.wait();
+        self.out.write_all(b"\x1b[?25h")?;
+        self.out.flush()?;
+        Ok((0, None))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn braille_snapshot() {
+        // 5 x 6 pixels: a diagonal, and the full last row, on 3 x 2 cells
+        let mut t = vec![BACKGROUND; 5 * 6];
+        for i in 0..5 {
+            t[i * 5 + i] = 1;
+            t[5 * 5 + i] = 2;
+        }
+        assert_eq!(braille(&t, 5, 6), "\u{2811}\u{2884}\u{2800}\n\
+                                       \u{2812}\u{2812}\u{2803}\n");
+    }
+
+    #[test]
+    fn braille_of_an_empty_frame() {
+        assert_eq!(braille(&[], 0, 0), "");
+        assert_eq!(braille(&[BACKGROUND; 3], 3, 1), "\u{2800}\u{2800}\n");
+    }
+}
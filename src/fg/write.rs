@@ -0,0 +1,206 @@
+use std::fs::File;
+use std::io::{self, Write};
+
+use super::fourier::CoeffsSet;
+use super::read::{PointsSet, CoeffsHeader, FileFormat};
+use super::json::Json;
+
+/// Returns the points of set formatted as `read::read_file` expects them, one
+/// per line: `t: (x, y)`, or `t: l (x, y)` if the interpolation after the
+/// point is linear, under their boundary if any.
+pub fn points_to_string(set: &PointsSet) -> String {
+    let mut s = String::new();
+    if let Some(boundary) = set.boundary.as_ref() {
+        s.push_str(&format!("# boundary: {}\n", boundary));
+    }
+    for i in 0..set.tt.len() {
+        let method = if set.mm[i] { "l " } else { "" };
+        s.push_str(&format!("{}: {}({}, {})\n", set.tt[i], method, set.xx[i], set.yy[i]));
+    }
+    s
+}
+
+/// Returns the points of set written in the columns `t x y`, separated by
+/// spaces as `dlmread(name, ' ')` reads them, or by commas under a header row
+/// if csv. The column of the interpolation method after the points, 1 if it
+/// is linear and 0 otherwise, is only written if some are, the last point
+/// included, as it gives the part closing a periodic drawing. It is read back
+/// with the header, or as the fourth column given to `read::parse_columns`.
+/// The boundary, if any, is written first as a `# boundary:` line, that
+/// `dlmread` has to skip as a header row.
+pub fn points_to_columns(set: &PointsSet, csv: bool) -> String {
+    let separator = if csv { "," } else { " " };
+    let modes = set.mm.iter().any(|&m| m);
+    let mut s = String::new();
+    if let Some(boundary) = set.boundary.as_ref() {
+        s.push_str(&format!("# boundary: {}\n", boundary));
+    }
+    if csv {
+        s.push_str(if modes { "t,x,y,mode\n" } else { "t,x,y\n" });
+    }
+    for i in 0..set.tt.len() {
+        s.push_str(&[set.tt[i], set.xx[i], set.yy[i]].iter().map(|v| v.to_string())
+                                                     .collect::<Vec<String>>()
+                                                     .join(separator));
+        if modes {
+            s.push_str(separator);
+            s.push_str(if set.mm[i] { "1" } else { "0" });
+        }
+        s.push('\n');
+    }
+    s
+}
+
+/// Returns the coefficients of set formatted as `read::read_fourier_coeffs`
+/// expects them, under their header. They are read back exactly, cf the
+/// `Display` of `CoeffsSet`. The number of terms is always written, so that
+/// truncated files are noticed, whatever `header.terms` holds.
+pub fn coeffs_to_string(set: &CoeffsSet, header: &CoeffsHeader) -> String {
+    let body = set.to_string();
+    let mut s = String::new();
+    if let Some(period) = header.period {
+        s.push_str(&format!("# period: {}\n", period));
+    }
+    if let Some(origin) = header.origin {
+        s.push_str(&format!("# origin: {}\n", origin));
+    }
+    s.push_str(&format!("# terms: {}\n", body.lines().count()));
+    if let Some(source) = header.source.as_ref() {
+        s.push_str(&format!("# source: {}\n", source));
+    }
+    s.push_str(&body);
+    s
+}
+
+/// Returns the points of set as the JSON document `read::points_from_json`
+/// reads.
+pub fn points_to_json(set: &PointsSet) -> Json {
+    let mut members = Vec::new();
+    if let Some(boundary) = set.boundary.as_ref() {
+        members.push((String::from("boundary"), Json::String(boundary.to_string())));
+    }
+    let points = (0..set.tt.len()).map(|i| Json::Object(vec![
+        (String::from("t"), Json::Number(set.tt[i])),
+        (String::from("x"), Json::Number(set.xx[i])),
+        (String::from("y"), Json::Number(set.yy[i])),
+        (String::from("mode"), Json::String(String::from(if set.mm[i] { "line" }
+                                                         else         { "spline" }))),
+    ])).collect();
+    members.push((String::from("points"), Json::Array(points)));
+    Json::Object(members)
+}
+
+/// Returns the coefficients of set, and their header, as the JSON document
+/// `read::coeffs_from_json` reads. The terms are c_0, c_1, c_-1, c_2, ...
+/// leaving out the ones that are 0, but c_0 and the last c_k, that give the
+/// size of the set, as the text format does.
+pub fn coeffs_to_json(set: &CoeffsSet, header: &CoeffsHeader) -> Json {
+    let n = set.ppos.len();
+    let mut all = Vec::with_capacity(2 * n);
+    if n > 0 {
+        all.push((0, set.ppos[0]));
+    }
+    for k in 1..n {
+        all.push(( k as i64, set.ppos[k]));
+        all.push((-(k as i64), set.nneg[k]));
+    }
+
+    let terms = all.into_iter()
+        .filter(|&(k, c)| c.re != 0.0 || c.im != 0.0 || k == 0 || k == n as i64 - 1)
+        .map(|(k, c)| Json::Object(vec![
+            (String::from("k"), Json::Number(k as f64)),
+            (String::from("re"), Json::Number(c.re)),
+            (String::from("im"), Json::Number(c.im)),
+            (String::from("magnitude"), Json::Number(c.modulus())),
+            (String::from("phase"), Json::Number(c.arg())),
+        ]))
+        .collect();
+
+    let mut members = Vec::new();
+    if let Some(period) = header.period {
+        members.push((String::from("period"), Json::Number(period)));
+    }
+    if let Some(origin) = header.origin {
+        members.push((String::from("origin"), Json::Number(origin)));
+    }
+    if let Some(source) = header.source.as_ref() {
+        members.push((String::from("source"), Json::String(source.clone())));
+    }
+    members.push((String::from("terms"), Json::Array(terms)));
+    Json::Object(members)
+}
+
+/// Writes the set of points in the file named filename, in the given format.
+pub fn write_file(filename: &str, set: &PointsSet, format: FileFormat)
+    -> Result<(), io::Error> {
+    let s = match format {
+        FileFormat::Text => points_to_string(set),
+        FileFormat::Json => points_to_json(set).to_pretty_string(),
+        FileFormat::Columns =>
+            points_to_columns(set, filename.to_lowercase().ends_with(".csv")),
+        FileFormat::Svg  => return Err(svg_output()),
+    };
+    File::create(filename)?.write_all(s.as_bytes())
+}
+
+/// Writes the Fourier coefficients in the file named filename, under the
+/// header, in the given format.
+pub fn write_fourier_coeffs(filename: &str, set: &CoeffsSet, header: &CoeffsHeader,
+    format: FileFormat) -> Result<(), io::Error> {
+    let s = match format {
+        FileFormat::Text => coeffs_to_string(set, header),
+        FileFormat::Json => coeffs_to_json(set, header).to_pretty_string(),
+        FileFormat::Columns => return Err(io::Error::new(io::ErrorKind::InvalidInput,
+            "columns only hold points")),
+        FileFormat::Svg  => return Err(svg_output()),
+    };
+    File::create(filename)?.write_all(s.as_bytes())
+}
+
+/* SVG paths are only read, drawings being written by `render` */
+fn svg_output() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, "SVG paths can only be read")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::read::{parse_columns, ColumnMap};
+    use super::super::spline::Boundary;
+
+    fn points(mm: Vec<bool>) -> PointsSet {
+        PointsSet { xx: vec![40.0, 0.0, -40.5], yy: vec![0.0, 40.0, 1e-3],
+                    tt: vec![0.0, 2.0, 4.0], mm, boundary: None }
+    }
+
+    #[test]
+    fn columns_keep_the_mode_of_the_last_point() {
+        for csv in [false, true] {
+            let columns = points_to_columns(&points(vec![false, false, true]), csv);
+            let map = if csv { ColumnMap::default() }
+                      else   { "1,2,3,4".parse::<ColumnMap>().ok().unwrap() };
+            let set = parse_columns(&columns, &map).ok().unwrap();
+            assert_eq!(set.mm, vec![false, false, true], "{}", columns);
+            assert_eq!(set.tt, vec![0.0, 2.0, 4.0]);
+            assert_eq!(set.xx, vec![40.0, 0.0, -40.5]);
+            assert_eq!(set.yy, vec![0.0, 40.0, 1e-3]);
+        }
+        let columns = points_to_columns(&points(vec![false; 3]), true);
+        assert_eq!(columns.lines().next(), Some("t,x,y"));
+    }
+
+    #[test]
+    fn columns_keep_the_boundary() {
+        for boundary in [None, Some(Boundary::Natural),
+                         Some(Boundary::Clamped(vec![1.5, -2.0], vec![0.0, 3.0]))] {
+            for csv in [false, true] {
+                let mut set = points(vec![false; 3]);
+                set.boundary = boundary.clone();
+                let read = parse_columns(&points_to_columns(&set, csv), &ColumnMap::default())
+                    .ok().unwrap();
+                assert_eq!(read.boundary, boundary);
+                assert_eq!(read.tt.len(), 3);
+            }
+        }
+    }
+}